@@ -0,0 +1,73 @@
+//! Compares the memory layouts of [LayoutGrid] on access patterns of large grids, and the cost of
+//! converting between them. Run with `cargo bench --bench layout`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use grid_util::layout::{Layout, LayoutGrid, Linear, Tiled, ZOrder};
+use grid_util::{Grid, Neighborhood, Point, ValueAdapter, ValueGrid};
+
+const SIZE: usize = 1024;
+
+fn grid<L: Layout>() -> LayoutGrid<u32, L> {
+    let values = (0..(SIZE * SIZE) as u32).map(|v| v % 7).collect();
+    LayoutGrid::<u32>::from_values(SIZE, SIZE, values)
+        .unwrap()
+        .relayout()
+}
+
+/// Sums the 3x3 neighbourhood of every cell, visiting the cells in the order of the layout.
+fn box_sum<L: Layout>(grid: &LayoutGrid<u32, L>) -> u64 {
+    let mut total = 0;
+    for ix in 0..grid.values.len() {
+        if let Some((x, y)) = L::cell(grid.width, grid.height, ix) {
+            let p = Point::new(x as i32, y as i32);
+            for n in p.moore_neighborhood_array() {
+                total += *grid.get_point(n).unwrap_or(&0) as u64;
+            }
+        }
+    }
+    total
+}
+
+fn bench_access<L: Layout>(c: &mut Criterion, name: &str) {
+    let mut group = c.benchmark_group("access");
+    group.sample_size(20);
+    let source = grid::<L>();
+    group.bench_function(BenchmarkId::new("box_sum", name), |b| {
+        b.iter(|| box_sum(black_box(&source)))
+    });
+    group.bench_function(BenchmarkId::new("flood_fill", name), |b| {
+        b.iter_batched_ref(
+            || ValueAdapter(source.clone()),
+            |g| g.flood_fill(Point::new(0, 0), Neighborhood::VonNeumann, |v| v != 9, 9),
+            criterion::BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+fn bench_relayout(c: &mut Criterion) {
+    let mut group = c.benchmark_group("relayout");
+    group.sample_size(20);
+    let linear = grid::<Linear>();
+    let z = grid::<ZOrder>();
+    group.bench_function("linear_to_zorder", |b| {
+        b.iter(|| black_box(&linear).relayout::<ZOrder>())
+    });
+    group.bench_function("linear_to_tiled32", |b| {
+        b.iter(|| black_box(&linear).relayout::<Tiled<32>>())
+    });
+    group.bench_function("zorder_to_linear", |b| {
+        b.iter(|| black_box(&z).relayout::<Linear>())
+    });
+    group.finish();
+}
+
+fn bench_layouts(c: &mut Criterion) {
+    bench_access::<Linear>(c, "linear");
+    bench_access::<ZOrder>(c, "zorder");
+    bench_access::<Tiled<16>>(c, "tiled16");
+    bench_access::<Tiled<32>>(c, "tiled32");
+}
+
+criterion_group!(benches, bench_layouts, bench_relayout);
+criterion_main!(benches);
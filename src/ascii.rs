@@ -0,0 +1,265 @@
+//! Parsing and rendering of grids as ASCII art, one character per cell.
+//!
+//! By default the first line of the text is the row with the highest `y`, so that maps read the
+//! same way as they are printed with [NORTH](crate::Direction::NORTH) pointing up. [RowOrder]
+//! flips this for text listing the rows from `y = 0` onwards.
+
+use crate::error::GridError;
+use crate::grid::{BoolGrid, GridDims, SimpleGrid, SimpleValueGrid, ValueGrid};
+use crate::point::Point;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Order in which the lines of ASCII art list the rows of a grid.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RowOrder {
+    /// The first line is the row with the highest `y`.
+    #[default]
+    NorthFirst,
+    /// The first line is the row at `y = 0`.
+    SouthFirst,
+}
+
+impl RowOrder {
+    /// Gives the `y` coordinate of the row on a given line of text, counting from 0.
+    fn row(&self, line: usize, height: usize) -> i32 {
+        match self {
+            RowOrder::NorthFirst => (height - 1 - line) as i32,
+            RowOrder::SouthFirst => line as i32,
+        }
+    }
+}
+
+/// Parses ASCII art into its dimensions and row-major values. Empty lines before and after the
+/// map are skipped, while lines of only spaces are rows of the map.
+pub(crate) fn parse<T>(
+    text: &str,
+    order: RowOrder,
+    mut symbol: impl FnMut(Point, char) -> Result<T, GridError>,
+) -> Result<(usize, usize, Vec<T>), GridError> {
+    let lines: Vec<&str> = text.lines().map(|l| l.trim_end_matches('\r')).collect();
+    let first = lines.iter().position(|l| !l.is_empty());
+    let last = lines.iter().rposition(|l| !l.is_empty());
+    let (Some(first), Some(last)) = (first, last) else {
+        return Ok((0, 0, Vec::new()));
+    };
+    let lines = &lines[first..=last];
+    let width = lines[0].chars().count();
+    let height = lines.len();
+    for (i, line) in lines.iter().enumerate() {
+        let found = line.chars().count();
+        if found != width {
+            return Err(GridError::RaggedLine {
+                line: first + i + 1,
+                expected: width,
+                found,
+            });
+        }
+    }
+    let mut values = Vec::with_capacity(width * height);
+    for y in 0..height as i32 {
+        // Mapping between lines and rows is its own inverse.
+        let line = lines[order.row(y as usize, height) as usize];
+        for (x, c) in line.chars().enumerate() {
+            values.push(symbol(Point::new(x as i32, y), c)?);
+        }
+    }
+    Ok((width, height, values))
+}
+
+/// Renders a grid of the given dimensions as ASCII art, with the lines separated by newlines.
+fn render(width: usize, height: usize, order: RowOrder, symbol: impl Fn(Point) -> char) -> String {
+    let mut text = String::with_capacity((width + 1) * height);
+    for line in 0..height {
+        if line > 0 {
+            text.push('\n');
+        }
+        let y = order.row(line, height);
+        text.extend((0..width as i32).map(|x| symbol(Point::new(x, y))));
+    }
+    text
+}
+
+impl<T> SimpleGrid<T> {
+    /// Parses ASCII art with the first line as the top row (see [RowOrder::NorthFirst]), mapping
+    /// each character to a value.
+    pub fn from_ascii(text: &str, symbol: impl FnMut(char) -> T) -> Result<Self, GridError> {
+        Self::from_ascii_with(text, RowOrder::NorthFirst, symbol)
+    }
+    /// Like [from_ascii](Self::from_ascii) with the given order of the rows.
+    pub fn from_ascii_with(
+        text: &str,
+        order: RowOrder,
+        mut symbol: impl FnMut(char) -> T,
+    ) -> Result<Self, GridError> {
+        let (width, height, values) = parse(text, order, |_, c| Ok(symbol(c)))?;
+        SimpleGrid::from_values(width, height, values)
+    }
+    /// Renders the grid as ASCII art with the top row first, the inverse of
+    /// [from_ascii](Self::from_ascii).
+    pub fn to_ascii(&self, symbol: impl Fn(&T) -> char) -> String {
+        self.to_ascii_with(RowOrder::NorthFirst, symbol)
+    }
+    /// Like [to_ascii](Self::to_ascii) with the given order of the rows.
+    pub fn to_ascii_with(&self, order: RowOrder, symbol: impl Fn(&T) -> char) -> String {
+        render(self.width, self.height, order, |p| {
+            symbol(&self.values[self.compute_ix(p.x, p.y)])
+        })
+    }
+}
+
+impl<T: Clone + Copy> SimpleValueGrid<T> {
+    /// Parses ASCII art with the first line as the top row (see [RowOrder::NorthFirst]), mapping
+    /// each character to a value.
+    pub fn from_ascii(text: &str, symbol: impl FnMut(char) -> T) -> Result<Self, GridError> {
+        Self::from_ascii_with(text, RowOrder::NorthFirst, symbol)
+    }
+    /// Like [from_ascii](Self::from_ascii) with the given order of the rows.
+    pub fn from_ascii_with(
+        text: &str,
+        order: RowOrder,
+        mut symbol: impl FnMut(char) -> T,
+    ) -> Result<Self, GridError> {
+        let (width, height, values) = parse(text, order, |_, c| Ok(symbol(c)))?;
+        SimpleValueGrid::from_values(width, height, values)
+    }
+    /// Renders the grid as ASCII art with the top row first, the inverse of
+    /// [from_ascii](Self::from_ascii).
+    pub fn to_ascii(&self, symbol: impl Fn(T) -> char) -> String {
+        self.to_ascii_with(RowOrder::NorthFirst, symbol)
+    }
+    /// Like [to_ascii](Self::to_ascii) with the given order of the rows.
+    pub fn to_ascii_with(&self, order: RowOrder, symbol: impl Fn(T) -> char) -> String {
+        render(self.width, self.height, order, |p| {
+            symbol(self.get_point(p))
+        })
+    }
+}
+
+impl BoolGrid {
+    /// Parses ASCII art with the first line as the top row (see [RowOrder::NorthFirst]), setting
+    /// the cells for which the predicate holds on their character.
+    pub fn from_ascii(text: &str, symbol: impl FnMut(char) -> bool) -> Result<Self, GridError> {
+        Self::from_ascii_with(text, RowOrder::NorthFirst, symbol)
+    }
+    /// Like [from_ascii](Self::from_ascii) with the given order of the rows.
+    pub fn from_ascii_with(
+        text: &str,
+        order: RowOrder,
+        mut symbol: impl FnMut(char) -> bool,
+    ) -> Result<Self, GridError> {
+        let (width, height, values) = parse(text, order, |_, c| Ok(symbol(c)))?;
+        Ok(Self::from_bools(width, height, &values))
+    }
+    /// Renders the grid as ASCII art with the top row first, the inverse of
+    /// [from_ascii](Self::from_ascii).
+    pub fn to_ascii(&self, symbol: impl Fn(bool) -> char) -> String {
+        self.to_ascii_with(RowOrder::NorthFirst, symbol)
+    }
+    /// Like [to_ascii](Self::to_ascii) with the given order of the rows.
+    pub fn to_ascii_with(&self, order: RowOrder, symbol: impl Fn(bool) -> char) -> String {
+        render(self.width, self.height, order, |p| {
+            symbol(self.get_point(p))
+        })
+    }
+    fn from_bools(width: usize, height: usize, values: &[bool]) -> BoolGrid {
+        let mut grid = BoolGrid::new(width, height, false);
+        for (ix, v) in values.iter().enumerate() {
+            if *v {
+                grid.set_ix(ix, true);
+            }
+        }
+        grid
+    }
+}
+
+/// Renders set cells as `#` and unset ones as `.`, with the top row first. The alternate form
+/// (`{:#}`) lists the rows from `y = 0` onwards instead.
+impl fmt::Display for BoolGrid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let order = if f.alternate() {
+            RowOrder::SouthFirst
+        } else {
+            RowOrder::NorthFirst
+        };
+        f.write_str(&self.to_ascii_with(order, |v| if v { '#' } else { '.' }))
+    }
+}
+
+/// Parses the output of [Display](fmt::Display), with the top row first.
+impl FromStr for BoolGrid {
+    type Err = GridError;
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (width, height, values) = parse(text, RowOrder::NorthFirst, |p, c| match c {
+            '#' => Ok(true),
+            '.' => Ok(false),
+            symbol => Err(GridError::InvalidSymbol {
+                symbol,
+                x: p.x,
+                y: p.y,
+            }),
+        })?;
+        Ok(BoolGrid::from_bools(width, height, &values))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Grid;
+
+    const MAP: &str = "
+#..
+..@
+";
+
+    #[test]
+    fn test_ascii_round_trip() {
+        let grid = SimpleGrid::from_ascii(MAP, |c| c).unwrap();
+        assert_eq!((grid.width, grid.height), (3, 2));
+        assert_eq!(grid.get(0, 1), Some(&'#'));
+        assert_eq!(grid.get(2, 0), Some(&'@'));
+        assert_eq!(grid.to_ascii(|c| *c), MAP.trim());
+
+        let flipped = SimpleGrid::from_ascii_with(MAP, RowOrder::SouthFirst, |c| c).unwrap();
+        assert_eq!(flipped.get(0, 0), Some(&'#'));
+        assert_eq!(flipped.to_ascii(|c| *c), "..@\n#..");
+
+        let walls = BoolGrid::from_ascii(MAP, |c| c == '#').unwrap();
+        assert!(walls.get(0, 1));
+        assert_eq!(walls.to_string(), "#..\n...");
+        assert_eq!(format!("{:#}", walls), "...\n#..");
+        assert_eq!("#..\n...".parse::<BoolGrid>().unwrap(), walls);
+
+        let values = SimpleValueGrid::from_ascii(MAP, |c| c as u8).unwrap();
+        assert_eq!(values.to_ascii(|v| v as char), MAP.trim());
+    }
+
+    #[test]
+    fn test_ascii_errors() {
+        assert_eq!(
+            SimpleGrid::from_ascii("\n##\n#\n", |c| c).unwrap_err(),
+            GridError::RaggedLine {
+                line: 3,
+                expected: 2,
+                found: 1
+            }
+        );
+        assert_eq!(
+            "#.\n.x".parse::<BoolGrid>().unwrap_err(),
+            GridError::InvalidSymbol {
+                symbol: 'x',
+                x: 1,
+                y: 0
+            }
+        );
+        assert_eq!(
+            "\n\n".parse::<BoolGrid>().unwrap(),
+            BoolGrid::new(0, 0, false)
+        );
+        let floor = SimpleGrid::from_ascii("   \n#.#", |c| c).unwrap();
+        assert_eq!((floor.width, floor.height), (3, 2));
+        assert_eq!(floor.get(1, 1), Some(&' '));
+    }
+}
@@ -0,0 +1,674 @@
+//! Compact little-endian binary format for [SimpleGrid], [SimpleValueGrid], [LayoutGrid],
+//! [BoolGrid], [OffsetGrid]'s of those and [ChunkedGrid].
+//!
+//! A file consists of a header followed by the values of the grid:
+//!
+//! | Bytes | Contents                                                    |
+//! |-------|-------------------------------------------------------------|
+//! | 4     | The magic number `GRID`                                     |
+//! | 2     | Format version, currently [FORMAT_VERSION]                  |
+//! | 1     | Element type tag, see [Element::TAG]                        |
+//! | 1     | [Compression] of the values                                 |
+//! | 1     | Kind of grid: 0 for plain, 1 for offset and 2 for chunked   |
+//!
+//! The header continues depending on the kind of grid:
+//!
+//! | Kind    | Bytes | Contents                                          |
+//! |---------|-------|---------------------------------------------------|
+//! | plain   | 8, 8  | Width and height                                  |
+//! | offset  | 4, 4  | `x` and `y` of the origin, as `i32`               |
+//! |         | 8, 8  | Width and height of the wrapped grid              |
+//! | chunked | 8     | Number of chunks                                  |
+//! |         | any   | The default value                                 |
+//!
+//! Without compression the values follow in row-major order, while with [Compression::Rle] they
+//! are written as runs of a `u32` count followed by the value. A [BoolGrid] is written as its
+//! `u64` words, including the padding word of grids with a multiple of 64 cells. A [LayoutGrid] is
+//! written in row-major order like a [SimpleGrid], so either can read the other's files. The
+//! chunks of a [ChunkedGrid] follow one another, each as the `x` and `y` of the chunk as `i32`
+//! followed by its [CHUNK_SIZE] by [CHUNK_SIZE] values, compressed separately.
+//!
+//! Reading checks the data like [deserialization](crate::validate) does, including the limit on
+//! the number of cells. Readers check the version right after the magic number, before the rest of
+//! the header. When the layout changes, the version is increased and the readers for earlier
+//! versions are kept, so that old files can still be read. Version 1 lacked the kind of grid, as
+//! only plain grids were supported.
+
+use crate::chunked::{ChunkedGrid, CHUNK_SIZE};
+use crate::error::GridError;
+use crate::grid::{BoolGrid, SimpleGrid, SimpleValueGrid};
+use crate::layout::{Layout, LayoutGrid};
+use crate::offset::OffsetGrid;
+use crate::point::Point;
+use crate::validate::check_size;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read, Write};
+
+/// Magic number at the start of every file.
+pub const MAGIC: [u8; 4] = *b"GRID";
+/// Version of the format written by this crate.
+pub const FORMAT_VERSION: u16 = 2;
+/// Element type tag of the words of a [BoolGrid].
+const BITS_TAG: u8 = 12;
+/// Kind of grid of a [SimpleGrid], [SimpleValueGrid], [LayoutGrid] or [BoolGrid].
+const PLAIN_KIND: u8 = 0;
+/// Kind of grid of an [OffsetGrid].
+const OFFSET_KIND: u8 = 1;
+/// Kind of grid of a [ChunkedGrid].
+const CHUNKED_KIND: u8 = 2;
+
+/// Compression of the values of a grid.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Compression {
+    #[default]
+    None,
+    /// Run-length encoding, which suits grids with large areas of equal values.
+    Rle,
+}
+
+impl Compression {
+    fn tag(&self) -> u8 {
+        match self {
+            Compression::None => 0,
+            Compression::Rle => 1,
+        }
+    }
+    fn from_tag(tag: u8) -> Option<Compression> {
+        match tag {
+            0 => Some(Compression::None),
+            1 => Some(Compression::Rle),
+            _ => None,
+        }
+    }
+}
+
+/// Values which can be stored in the binary format.
+pub trait Element: Sized {
+    /// Tag identifying the type in the header, so that a file is not read as the wrong type.
+    const TAG: u8;
+    fn write_le(&self, writer: &mut impl Write) -> io::Result<()>;
+    fn read_le(reader: &mut impl Read) -> io::Result<Self>;
+}
+
+macro_rules! impl_element {
+    ($($t:ty => $tag:expr),*) => {
+        $(
+            impl Element for $t {
+                const TAG: u8 = $tag;
+                fn write_le(&self, writer: &mut impl Write) -> io::Result<()> {
+                    writer.write_all(&self.to_le_bytes())
+                }
+                fn read_le(reader: &mut impl Read) -> io::Result<Self> {
+                    let mut bytes = [0; std::mem::size_of::<$t>()];
+                    reader.read_exact(&mut bytes)?;
+                    Ok(<$t>::from_le_bytes(bytes))
+                }
+            }
+        )*
+    };
+}
+
+impl_element!(
+    u8 => 1, u16 => 2, u32 => 3, u64 => 4,
+    i8 => 5, i16 => 6, i32 => 7, i64 => 8,
+    f32 => 9, f64 => 10
+);
+
+impl Element for bool {
+    const TAG: u8 = 11;
+    fn write_le(&self, writer: &mut impl Write) -> io::Result<()> {
+        (*self as u8).write_le(writer)
+    }
+    fn read_le(reader: &mut impl Read) -> io::Result<Self> {
+        match u8::read_le(reader)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            b => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid bool {}", b),
+            )),
+        }
+    }
+}
+
+/// Errors that can occur when reading the binary format.
+#[derive(Debug)]
+pub enum BinaryError {
+    Io(io::Error),
+    /// The data does not start with [MAGIC].
+    BadMagic,
+    /// The data was written by a newer version of the format.
+    UnsupportedVersion(u16),
+    /// The data holds elements of another type than requested.
+    TypeMismatch {
+        expected: u8,
+        found: u8,
+    },
+    UnknownCompression(u8),
+    /// The data holds another kind of grid than requested, such as an [OffsetGrid] instead of a
+    /// [SimpleGrid].
+    KindMismatch {
+        expected: u8,
+        found: u8,
+    },
+    /// The values do not form a valid grid.
+    InvalidGrid(GridError),
+}
+
+impl fmt::Display for BinaryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BinaryError::Io(e) => write!(f, "{}", e),
+            BinaryError::BadMagic => write!(f, "not a grid file"),
+            BinaryError::UnsupportedVersion(v) => {
+                write!(f, "unsupported format version {}", v)
+            }
+            BinaryError::TypeMismatch { expected, found } => write!(
+                f,
+                "expected elements of type {} but found type {}",
+                expected, found
+            ),
+            BinaryError::UnknownCompression(c) => write!(f, "unknown compression {}", c),
+            BinaryError::KindMismatch { expected, found } => write!(
+                f,
+                "expected a grid of kind {} but found kind {}",
+                expected, found
+            ),
+            BinaryError::InvalidGrid(e) => write!(f, "invalid grid: {}", e),
+        }
+    }
+}
+
+impl Error for BinaryError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BinaryError::Io(e) => Some(e),
+            BinaryError::InvalidGrid(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for BinaryError {
+    fn from(e: io::Error) -> Self {
+        BinaryError::Io(e)
+    }
+}
+
+impl From<GridError> for BinaryError {
+    fn from(e: GridError) -> Self {
+        BinaryError::InvalidGrid(e)
+    }
+}
+
+fn write_header(
+    writer: &mut impl Write,
+    tag: u8,
+    compression: Compression,
+    kind: u8,
+) -> io::Result<()> {
+    writer.write_all(&MAGIC)?;
+    FORMAT_VERSION.write_le(writer)?;
+    tag.write_le(writer)?;
+    compression.tag().write_le(writer)?;
+    kind.write_le(writer)
+}
+
+fn write_dims(writer: &mut impl Write, width: usize, height: usize) -> io::Result<()> {
+    (width as u64).write_le(writer)?;
+    (height as u64).write_le(writer)
+}
+
+/// Reads the common part of the header, checking it against the expected element type and kind
+/// of grid and giving the compression.
+fn read_header(
+    reader: &mut impl Read,
+    expected: u8,
+    expected_kind: u8,
+) -> Result<Compression, BinaryError> {
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if magic != MAGIC {
+        return Err(BinaryError::BadMagic);
+    }
+    let version = u16::read_le(reader)?;
+    if !(1..=FORMAT_VERSION).contains(&version) {
+        return Err(BinaryError::UnsupportedVersion(version));
+    }
+    let found = u8::read_le(reader)?;
+    if found != expected {
+        return Err(BinaryError::TypeMismatch { expected, found });
+    }
+    let compression = u8::read_le(reader)?;
+    let compression =
+        Compression::from_tag(compression).ok_or(BinaryError::UnknownCompression(compression))?;
+    let kind = match version {
+        1 => PLAIN_KIND,
+        _ => u8::read_le(reader)?,
+    };
+    if kind != expected_kind {
+        return Err(BinaryError::KindMismatch {
+            expected: expected_kind,
+            found: kind,
+        });
+    }
+    Ok(compression)
+}
+
+/// Reads the dimensions of a grid, checking them against the limit on the number of cells.
+fn read_dims(reader: &mut impl Read) -> Result<(usize, usize), BinaryError> {
+    let width = usize::try_from(u64::read_le(reader)?).unwrap_or(usize::MAX);
+    let height = usize::try_from(u64::read_le(reader)?).unwrap_or(usize::MAX);
+    check_size(width, height)?;
+    Ok((width, height))
+}
+
+fn write_point(writer: &mut impl Write, point: Point) -> io::Result<()> {
+    point.x.write_le(writer)?;
+    point.y.write_le(writer)
+}
+
+fn read_point(reader: &mut impl Read) -> io::Result<Point> {
+    Ok(Point::new(i32::read_le(reader)?, i32::read_le(reader)?))
+}
+
+fn write_values<T: Element + PartialEq>(
+    writer: &mut impl Write,
+    values: &[T],
+    compression: Compression,
+) -> io::Result<()> {
+    match compression {
+        Compression::None => values.iter().try_for_each(|v| v.write_le(writer)),
+        Compression::Rle => {
+            let mut rest = values;
+            while let Some(first) = rest.first() {
+                let run = rest
+                    .iter()
+                    .take(u32::MAX as usize)
+                    .take_while(|v| *v == first)
+                    .count();
+                (run as u32).write_le(writer)?;
+                first.write_le(writer)?;
+                rest = &rest[run..];
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Largest number of values reserved before any are read.
+const MAX_PREALLOCATED: usize = 1 << 16;
+
+fn read_values<T: Element + Clone>(
+    reader: &mut impl Read,
+    len: usize,
+    compression: Compression,
+) -> Result<Vec<T>, BinaryError> {
+    // The length comes from the header, so only part of it is reserved up front and the values
+    // grow as they are read, keeping truncated files from allocating the full grid.
+    let mut values = Vec::with_capacity(len.min(MAX_PREALLOCATED));
+    match compression {
+        Compression::None => {
+            for _ in 0..len {
+                values.push(T::read_le(reader)?);
+            }
+        }
+        Compression::Rle => {
+            while values.len() < len {
+                let run = u32::read_le(reader)? as usize;
+                let value = T::read_le(reader)?;
+                if run == 0 || values.len() + run > len {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("run of {} values does not fit the grid", run),
+                    )
+                    .into());
+                }
+                values.extend(std::iter::repeat_n(value, run));
+            }
+        }
+    }
+    Ok(values)
+}
+
+impl<T: Element + PartialEq + Clone> SimpleGrid<T> {
+    /// Writes the grid in the binary format.
+    pub fn write_to(&self, mut writer: impl Write, compression: Compression) -> io::Result<()> {
+        write_header(&mut writer, T::TAG, compression, PLAIN_KIND)?;
+        self.write_body(&mut writer, compression)
+    }
+    /// Reads a grid in the binary format, as written by [write_to](Self::write_to),
+    /// [SimpleValueGrid::write_to] or [LayoutGrid::write_to].
+    pub fn read_from(mut reader: impl Read) -> Result<Self, BinaryError> {
+        let compression = read_header(&mut reader, T::TAG, PLAIN_KIND)?;
+        Self::read_body(&mut reader, compression)
+    }
+    fn write_body(&self, writer: &mut impl Write, compression: Compression) -> io::Result<()> {
+        write_dims(writer, self.width, self.height)?;
+        write_values(writer, &self.values, compression)
+    }
+    fn read_body(reader: &mut impl Read, compression: Compression) -> Result<Self, BinaryError> {
+        let (width, height) = read_dims(reader)?;
+        let values = read_values(reader, width * height, compression)?;
+        Ok(SimpleGrid::from_values(width, height, values)?)
+    }
+}
+
+impl<T: Element + PartialEq + Clone + Copy> SimpleValueGrid<T> {
+    /// Writes the grid in the binary format.
+    pub fn write_to(&self, mut writer: impl Write, compression: Compression) -> io::Result<()> {
+        write_header(&mut writer, T::TAG, compression, PLAIN_KIND)?;
+        self.write_body(&mut writer, compression)
+    }
+    /// Reads a grid in the binary format, as written by [write_to](Self::write_to),
+    /// [SimpleGrid::write_to] or [LayoutGrid::write_to].
+    pub fn read_from(mut reader: impl Read) -> Result<Self, BinaryError> {
+        let compression = read_header(&mut reader, T::TAG, PLAIN_KIND)?;
+        Self::read_body(&mut reader, compression)
+    }
+    fn write_body(&self, writer: &mut impl Write, compression: Compression) -> io::Result<()> {
+        write_dims(writer, self.width, self.height)?;
+        write_values(writer, &self.values, compression)
+    }
+    fn read_body(reader: &mut impl Read, compression: Compression) -> Result<Self, BinaryError> {
+        let (width, height) = read_dims(reader)?;
+        let values = read_values(reader, width * height, compression)?;
+        Ok(SimpleValueGrid::from_values(width, height, values)?)
+    }
+}
+
+impl<T: Element + PartialEq + Clone, L: Layout> LayoutGrid<T, L> {
+    /// Writes the grid in the binary format, with the values in row-major order whatever the
+    /// layout.
+    pub fn write_to(&self, mut writer: impl Write, compression: Compression) -> io::Result<()> {
+        write_header(&mut writer, T::TAG, compression, PLAIN_KIND)?;
+        self.write_body(&mut writer, compression)
+    }
+    /// Reads a grid in the binary format, as written by [write_to](Self::write_to) in any layout,
+    /// [SimpleGrid::write_to] or [SimpleValueGrid::write_to].
+    pub fn read_from(mut reader: impl Read) -> Result<Self, BinaryError> {
+        let compression = read_header(&mut reader, T::TAG, PLAIN_KIND)?;
+        Self::read_body(&mut reader, compression)
+    }
+    fn write_body(&self, writer: &mut impl Write, compression: Compression) -> io::Result<()> {
+        write_dims(writer, self.width, self.height)?;
+        write_values(writer, &self.to_values(), compression)
+    }
+    fn read_body(reader: &mut impl Read, compression: Compression) -> Result<Self, BinaryError> {
+        let (width, height) = read_dims(reader)?;
+        let values = read_values(reader, width * height, compression)?;
+        Ok(LayoutGrid::from_values(width, height, values)?)
+    }
+}
+
+impl BoolGrid {
+    /// Writes the grid in the binary format.
+    pub fn write_to(&self, mut writer: impl Write, compression: Compression) -> io::Result<()> {
+        write_header(&mut writer, BITS_TAG, compression, PLAIN_KIND)?;
+        self.write_body(&mut writer, compression)
+    }
+    /// Reads a grid in the binary format, as written by [write_to](Self::write_to).
+    pub fn read_from(mut reader: impl Read) -> Result<Self, BinaryError> {
+        let compression = read_header(&mut reader, BITS_TAG, PLAIN_KIND)?;
+        Self::read_body(&mut reader, compression)
+    }
+    fn write_body(&self, writer: &mut impl Write, compression: Compression) -> io::Result<()> {
+        write_dims(writer, self.width, self.height)?;
+        write_values(writer, &self.values, compression)
+    }
+    fn read_body(reader: &mut impl Read, compression: Compression) -> Result<Self, BinaryError> {
+        let (width, height) = read_dims(reader)?;
+        let values = read_values(reader, 1 + width * height / 64, compression)?;
+        let grid = BoolGrid {
+            width,
+            height,
+            values,
+        };
+        if grid.has_padding_set() {
+            return Err(GridError::PaddingSet { width, height }.into());
+        }
+        Ok(grid)
+    }
+}
+
+macro_rules! impl_offset_binary {
+    ($([$($generics:tt)*] $grid:ty => $tag:expr),*) => {
+        $(
+            impl<$($generics)*> OffsetGrid<$grid> {
+                /// Writes the grid in the binary format, with its origin.
+                pub fn write_to(
+                    &self,
+                    mut writer: impl Write,
+                    compression: Compression,
+                ) -> io::Result<()> {
+                    write_header(&mut writer, $tag, compression, OFFSET_KIND)?;
+                    write_point(&mut writer, self.origin)?;
+                    self.grid.write_body(&mut writer, compression)
+                }
+                /// Reads a grid in the binary format, as written by [write_to](Self::write_to).
+                pub fn read_from(mut reader: impl Read) -> Result<Self, BinaryError> {
+                    let compression = read_header(&mut reader, $tag, OFFSET_KIND)?;
+                    let origin = read_point(&mut reader)?;
+                    Ok(OffsetGrid::at(origin, <$grid>::read_body(&mut reader, compression)?))
+                }
+            }
+        )*
+    };
+}
+
+impl_offset_binary!(
+    [T: Element + PartialEq + Clone] SimpleGrid<T> => T::TAG,
+    [T: Element + PartialEq + Clone + Copy] SimpleValueGrid<T> => T::TAG,
+    [T: Element + PartialEq + Clone, L: Layout] LayoutGrid<T, L> => T::TAG,
+    [] BoolGrid => BITS_TAG
+);
+
+impl<T: Element + PartialEq + Clone> ChunkedGrid<T> {
+    /// Writes the grid in the binary format, with the chunks ordered by their coordinates so that
+    /// equal grids give equal files.
+    pub fn write_to(&self, mut writer: impl Write, compression: Compression) -> io::Result<()> {
+        write_header(&mut writer, T::TAG, compression, CHUNKED_KIND)?;
+        (self.chunk_count() as u64).write_le(&mut writer)?;
+        self.default_value().write_le(&mut writer)?;
+        let mut chunks: Vec<(Point, &[T])> = self.chunks().collect();
+        chunks.sort_by_key(|(chunk, _)| *chunk);
+        for (chunk, values) in chunks {
+            write_point(&mut writer, chunk)?;
+            write_values(&mut writer, values, compression)?;
+        }
+        Ok(())
+    }
+    /// Reads a grid in the binary format, as written by [write_to](Self::write_to).
+    pub fn read_from(mut reader: impl Read) -> Result<Self, BinaryError> {
+        let compression = read_header(&mut reader, T::TAG, CHUNKED_KIND)?;
+        let count = usize::try_from(u64::read_le(&mut reader)?).unwrap_or(usize::MAX);
+        // The chunks are counted as if stacked into a single column, as when deserializing.
+        check_size(CHUNK_SIZE, CHUNK_SIZE.saturating_mul(count))?;
+        let default_value = T::read_le(&mut reader)?;
+        let mut chunks =
+            HashMap::with_capacity(count.min(MAX_PREALLOCATED / (CHUNK_SIZE * CHUNK_SIZE)));
+        for _ in 0..count {
+            let chunk = read_point(&mut reader)?;
+            let values = read_values(&mut reader, CHUNK_SIZE * CHUNK_SIZE, compression)?;
+            if chunks.insert(chunk, values).is_some() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("chunk {} appears twice", chunk),
+                )
+                .into());
+            }
+        }
+        Ok(ChunkedGrid::from_chunks(default_value, chunks))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{Grid, GridWrite, ValueGrid};
+    use crate::layout::ZOrder;
+    use crate::rect::Rect;
+
+    #[test]
+    fn test_binary_round_trip() {
+        let mut grid = SimpleGrid::new(50, 40, 0u16);
+        grid.set_rect(Rect::new(10, 10, 5, 5), 300);
+        let mut raw = Vec::new();
+        grid.write_to(&mut raw, Compression::None).unwrap();
+        assert_eq!(raw.len(), 25 + 50 * 40 * 2);
+        let mut rle = Vec::new();
+        grid.write_to(&mut rle, Compression::Rle).unwrap();
+        assert!(rle.len() < raw.len() / 10);
+        for data in [&raw, &rle] {
+            let read = SimpleGrid::<u16>::read_from(data.as_slice()).unwrap();
+            assert_eq!(read.values, grid.values);
+            let values = SimpleValueGrid::<u16>::read_from(data.as_slice()).unwrap();
+            assert_eq!(values.values, grid.values);
+        }
+
+        let mut bools = BoolGrid::new(64, 3, false);
+        bools.set(63, 2, true);
+        for compression in [Compression::None, Compression::Rle] {
+            let mut data = Vec::new();
+            bools.write_to(&mut data, compression).unwrap();
+            assert_eq!(BoolGrid::read_from(data.as_slice()).unwrap(), bools);
+        }
+
+        let zorder = LayoutGrid::<u16, ZOrder>::from_values(50, 40, grid.values.clone()).unwrap();
+        let mut data = Vec::new();
+        zorder.write_to(&mut data, Compression::Rle).unwrap();
+        assert_eq!(data, rle);
+        assert_eq!(
+            LayoutGrid::<u16, ZOrder>::read_from(data.as_slice()).unwrap(),
+            zorder
+        );
+    }
+
+    #[test]
+    fn test_binary_offset_and_chunked() {
+        let mut offset = OffsetGrid::at(Point::new(-7, 300), SimpleValueGrid::new(6, 4, 2i8));
+        offset.set(-6, 301, -1);
+        let mut data = Vec::new();
+        offset.write_to(&mut data, Compression::None).unwrap();
+        let read = OffsetGrid::<SimpleValueGrid<i8>>::read_from(data.as_slice()).unwrap();
+        assert_eq!(read.origin, offset.origin);
+        assert_eq!(read.grid.values, offset.grid.values);
+        assert!(matches!(
+            SimpleValueGrid::<i8>::read_from(data.as_slice()),
+            Err(BinaryError::KindMismatch {
+                expected: 0,
+                found: 1
+            })
+        ));
+        let bools = OffsetGrid::at(Point::new(3, -3), BoolGrid::new(70, 2, true));
+        let mut data = Vec::new();
+        bools.write_to(&mut data, Compression::Rle).unwrap();
+        assert_eq!(
+            OffsetGrid::<BoolGrid>::read_from(data.as_slice()).unwrap(),
+            bools
+        );
+
+        let mut chunked = ChunkedGrid::empty(5u32);
+        chunked.set(-100, 40, 1);
+        chunked.set(i32::MAX, i32::MIN, 2);
+        chunked.set(0, 0, 3);
+        for compression in [Compression::None, Compression::Rle] {
+            let mut data = Vec::new();
+            chunked.write_to(&mut data, compression).unwrap();
+            let read = ChunkedGrid::<u32>::read_from(data.as_slice()).unwrap();
+            assert_eq!(read.chunk_count(), 3);
+            assert_eq!(read.default_value(), &5);
+            assert_eq!(read.bounding_rect(), chunked.bounding_rect());
+            for (chunk, values) in chunked.chunks() {
+                assert_eq!(read.chunk(chunk), Some(values));
+            }
+            let mut again = Vec::new();
+            read.write_to(&mut again, compression).unwrap();
+            assert_eq!(again, data);
+        }
+        let mut data = Vec::new();
+        chunked.write_to(&mut data, Compression::Rle).unwrap();
+        // The chunk count is right after the header, so doubling it runs past the data.
+        data[9..17].copy_from_slice(&6u64.to_le_bytes());
+        assert!(matches!(
+            ChunkedGrid::<u32>::read_from(data.as_slice()),
+            Err(BinaryError::Io(_))
+        ));
+        data[9..17].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(matches!(
+            ChunkedGrid::<u32>::read_from(data.as_slice()),
+            Err(BinaryError::InvalidGrid(GridError::TooLarge { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_binary_version_1() {
+        let grid = SimpleGrid::from_values(2, 1, vec![4u8, 9]).unwrap();
+        let mut data = Vec::new();
+        grid.write_to(&mut data, Compression::None).unwrap();
+        // Version 1 had no kind of grid in the header.
+        data[4..6].copy_from_slice(&1u16.to_le_bytes());
+        data.remove(8);
+        assert_eq!(
+            SimpleGrid::<u8>::read_from(data.as_slice()).unwrap().values,
+            grid.values
+        );
+        assert!(matches!(
+            OffsetGrid::<SimpleGrid<u8>>::read_from(data.as_slice()),
+            Err(BinaryError::KindMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_binary_errors() {
+        let grid = SimpleGrid::new(3, 2, 1.5f32);
+        let mut data = Vec::new();
+        grid.write_to(&mut data, Compression::Rle).unwrap();
+        assert!(matches!(
+            SimpleGrid::<i32>::read_from(data.as_slice()),
+            Err(BinaryError::TypeMismatch {
+                expected: 7,
+                found: 9
+            })
+        ));
+        assert!(matches!(
+            SimpleGrid::<f32>::read_from(&data[..data.len() - 1]),
+            Err(BinaryError::Io(_))
+        ));
+        let mut newer = data.clone();
+        newer[4] = 3;
+        assert!(matches!(
+            SimpleGrid::<f32>::read_from(newer.as_slice()),
+            Err(BinaryError::UnsupportedVersion(3))
+        ));
+        assert!(matches!(
+            SimpleGrid::<f32>::read_from(&b"RON("[..]),
+            Err(BinaryError::BadMagic)
+        ));
+
+        // A header claiming a large grid without its values fails without reserving the grid.
+        let mut header = Vec::new();
+        SimpleGrid::new(0, 0, 0.0f64)
+            .write_to(&mut header, Compression::None)
+            .unwrap();
+        header[9..17].copy_from_slice(&16384u64.to_le_bytes());
+        header[17..25].copy_from_slice(&16384u64.to_le_bytes());
+        assert!(matches!(
+            SimpleGrid::<f64>::read_from(header.as_slice()),
+            Err(BinaryError::Io(_))
+        ));
+
+        let mut bools = Vec::new();
+        BoolGrid::new(2, 2, false)
+            .write_to(&mut bools, Compression::None)
+            .unwrap();
+        bools[25] = 16;
+        assert!(matches!(
+            BoolGrid::read_from(bools.as_slice()),
+            Err(BinaryError::InvalidGrid(GridError::PaddingSet { .. }))
+        ));
+    }
+}
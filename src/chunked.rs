@@ -0,0 +1,488 @@
+//! Sparse [ChunkedGrid] spanning all `i32` coordinates, which allocates square chunks of cells
+//! as they are written to.
+
+use crate::components::{self, ComponentInfo};
+use crate::error::GridError;
+use crate::grid::{Grid, GridDims, GridRead, GridWrite, SimpleValueGrid};
+use crate::offset::translate_rect;
+use crate::point::{Neighborhood, Point};
+use crate::rect::Rect;
+use crate::validate::ChunkedGridData;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Width and height of the chunks of a [ChunkedGrid].
+pub const CHUNK_SIZE: usize = 32;
+
+/// [Grid] without fixed bounds, storing its cells in chunks of [CHUNK_SIZE] by [CHUNK_SIZE] cells
+/// keyed by their chunk coordinates (see [chunk_of](Self::chunk_of)). Cells of chunks that are not
+/// allocated hold the default value.
+///
+/// Every coordinate is in bounds. Reading never allocates, while [get_mut](Grid::get_mut) and
+/// everything built on it allocate the chunk of the cell. The [rect](GridDims::rect),
+/// [width](GridDims::width) and [height](GridDims::height) are those of the
+/// [bounding rectangle](Self::bounding_rect), and the iterators of [Grid],
+/// [flood_fill](Grid::flood_fill) and [label_components](Grid::label_components) are limited to
+/// its cells. Indices, as given by [compute_ix](GridDims::compute_ix), are row-major from the
+/// lower corner of the bounding rectangle, so they change as chunks are added or dropped.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(try_from = "ChunkedGridData<T>")]
+pub struct ChunkedGrid<T> {
+    default_value: T,
+    chunks: HashMap<Point, Vec<T>>,
+    /// Cached [bounding rectangle](Self::bounding_rect) of the chunks.
+    #[serde(skip)]
+    bounds: Option<Rect>,
+}
+
+impl<T> ChunkedGrid<T> {
+    /// Creates a grid without any allocated chunks.
+    pub fn empty(default_value: T) -> ChunkedGrid<T> {
+        ChunkedGrid {
+            default_value,
+            chunks: HashMap::new(),
+            bounds: None,
+        }
+    }
+    /// Creates a grid from allocated chunks, which must hold [CHUNK_SIZE] by [CHUNK_SIZE] values
+    /// each.
+    pub(crate) fn from_chunks(default_value: T, chunks: HashMap<Point, Vec<T>>) -> ChunkedGrid<T> {
+        let mut grid = ChunkedGrid {
+            default_value,
+            chunks,
+            bounds: None,
+        };
+        grid.update_bounds();
+        grid
+    }
+    /// The value of cells which have not been allocated.
+    pub fn default_value(&self) -> &T {
+        &self.default_value
+    }
+    /// Gives the coordinates of the chunk containing a cell.
+    pub fn chunk_of(point: Point) -> Point {
+        let size = CHUNK_SIZE as i32;
+        Point::new(point.x.div_euclid(size), point.y.div_euclid(size))
+    }
+    /// Gives the cells covered by a chunk, as inclusive corners (see [Rect::contains]).
+    pub fn chunk_rect(chunk: Point) -> Rect {
+        let size = CHUNK_SIZE as i32;
+        Rect {
+            x1: chunk.x * size,
+            y1: chunk.y * size,
+            x2: chunk.x * size + (size - 1),
+            y2: chunk.y * size + (size - 1),
+        }
+    }
+    /// The lower corner of the [bounding rectangle](Self::bounding_rect), or the origin if there
+    /// are no chunks.
+    fn origin(&self) -> Point {
+        self.bounds
+            .map_or(Point::new(0, 0), |r| Point::new(r.x1, r.y1))
+    }
+    /// Gives the cell of the [bounding rectangle](Self::bounding_rect) at an index, or `None` if
+    /// the index is past its cells.
+    fn ix_point(&self, ix: usize) -> Option<Point> {
+        let rect = self.bounds?;
+        let w = self.width();
+        if ix / w >= self.height() {
+            return None;
+        }
+        Some(Point::new(
+            (rect.x1 as i64 + (ix % w) as i64) as i32,
+            (rect.y1 as i64 + (ix / w) as i64) as i32,
+        ))
+    }
+    /// Recomputes the cached bounding rectangle from the chunks.
+    fn update_bounds(&mut self) {
+        self.bounds = self
+            .chunks
+            .keys()
+            .map(|c| Self::chunk_rect(*c))
+            .reduce(|a, b| Rect {
+                x1: a.x1.min(b.x1),
+                y1: a.y1.min(b.y1),
+                x2: a.x2.max(b.x2),
+                y2: a.y2.max(b.y2),
+            });
+    }
+    /// Index of a cell within the row-major values of its chunk.
+    fn offset_in_chunk(x: i32, y: i32) -> usize {
+        let size = CHUNK_SIZE as i32;
+        (x.rem_euclid(size) + y.rem_euclid(size) * size) as usize
+    }
+    /// Number of allocated chunks.
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+    /// Gives the row-major values of a chunk, if it is allocated.
+    pub fn chunk(&self, chunk: Point) -> Option<&[T]> {
+        self.chunks.get(&chunk).map(|values| values.as_slice())
+    }
+    /// Iterates over the allocated chunks and their row-major values, in no particular order.
+    pub fn chunks(&self) -> impl Iterator<Item = (Point, &[T])> {
+        self.chunks
+            .iter()
+            .map(|(chunk, values)| (*chunk, values.as_slice()))
+    }
+    /// Iterates mutably over the allocated chunks and their row-major values, in no particular
+    /// order.
+    pub fn chunks_mut(&mut self) -> impl Iterator<Item = (Point, &mut [T])> {
+        self.chunks
+            .iter_mut()
+            .map(|(chunk, values)| (*chunk, values.as_mut_slice()))
+    }
+    /// Drops a chunk, resetting its cells to the default value and giving its values if it was
+    /// allocated.
+    pub fn remove_chunk(&mut self, chunk: Point) -> Option<Vec<T>> {
+        let values = self.chunks.remove(&chunk);
+        if values.is_some() {
+            self.update_bounds();
+        }
+        values
+    }
+    /// Drops the chunks for which the predicate does not hold given their coordinates and values.
+    pub fn retain_chunks(&mut self, mut predicate: impl FnMut(Point, &[T]) -> bool) {
+        self.chunks
+            .retain(|chunk, values| predicate(*chunk, values));
+        self.update_bounds();
+    }
+    /// Drops all chunks.
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+        self.bounds = None;
+    }
+    /// Gives the smallest rectangle containing all allocated chunks, as inclusive corners (see
+    /// [Rect::contains]), or `None` if there are none.
+    pub fn bounding_rect(&self) -> Option<Rect> {
+        self.bounds
+    }
+}
+
+impl<T: Clone> ChunkedGrid<T> {
+    /// Gives mutable access to the values of a chunk, allocating it if needed.
+    pub fn chunk_mut(&mut self, chunk: Point) -> &mut [T] {
+        let default_value = &self.default_value;
+        let bounds = &mut self.bounds;
+        self.chunks.entry(chunk).or_insert_with(|| {
+            let r = Self::chunk_rect(chunk);
+            *bounds = Some(bounds.map_or(r, |b| Rect {
+                x1: b.x1.min(r.x1),
+                y1: b.y1.min(r.y1),
+                x2: b.x2.max(r.x2),
+                y2: b.y2.max(r.y2),
+            }));
+            vec![default_value.clone(); CHUNK_SIZE * CHUNK_SIZE]
+        })
+    }
+}
+
+impl<T> GridDims for ChunkedGrid<T> {
+    fn width(&self) -> usize {
+        self.bounds
+            .map_or(0, |r| (r.x2 as i64 - r.x1 as i64 + 1) as usize)
+    }
+    fn height(&self) -> usize {
+        self.bounds
+            .map_or(0, |r| (r.y2 as i64 - r.y1 as i64 + 1) as usize)
+    }
+    fn index_in_bounds(&self, _x: i32, _y: i32) -> bool {
+        true
+    }
+    /// Gives the index of a cell within the [bounding rectangle](ChunkedGrid::bounding_rect),
+    /// which is row-wise from its lower corner.
+    fn compute_ix(&self, x: i32, y: i32) -> usize {
+        let origin = self.origin();
+        (x as i64 - origin.x as i64) as usize + (y as i64 - origin.y as i64) as usize * self.width()
+    }
+    /// As [Rect::new] with the width and height, except that the upper corner stops at
+    /// `i32::MAX`.
+    fn rect(&self) -> Rect {
+        self.bounds.map_or(Rect::new(0, 0, 0, 0), |r| Rect {
+            x1: r.x1,
+            y1: r.y1,
+            x2: r.x2.saturating_add(1),
+            y2: r.y2.saturating_add(1),
+        })
+    }
+}
+
+impl<T: Clone> GridRead<T> for ChunkedGrid<T> {
+    fn read(&self, x: i32, y: i32) -> Option<T> {
+        self.get(x, y).cloned()
+    }
+}
+
+/// Writing allocates the chunk of the cell, so it always succeeds.
+impl<T: Clone> GridWrite<T> for ChunkedGrid<T> {
+    fn write(&mut self, x: i32, y: i32, value: T) -> Result<(), GridError> {
+        self.set(x, y, value);
+        Ok(())
+    }
+}
+
+impl<T: Clone> Grid<T> for ChunkedGrid<T> {
+    /// Creates a grid with the chunks covering the cells from the origin up to the width and height
+    /// allocated.
+    fn new(width: usize, height: usize, default_value: T) -> Self {
+        let mut grid = ChunkedGrid::empty(default_value);
+        if width > 0 && height > 0 {
+            let last = Self::chunk_of(Point::new(width as i32 - 1, height as i32 - 1));
+            for y in 0..=last.y {
+                for x in 0..=last.x {
+                    grid.chunk_mut(Point::new(x, y));
+                }
+            }
+        }
+        grid
+    }
+    fn get(&self, x: i32, y: i32) -> Option<&T> {
+        Some(match self.chunks.get(&Self::chunk_of(Point::new(x, y))) {
+            Some(values) => &values[Self::offset_in_chunk(x, y)],
+            None => &self.default_value,
+        })
+    }
+    fn get_ix(&self, ix: usize) -> Option<&T> {
+        self.get_point(self.ix_point(ix)?)
+    }
+    fn get_mut(&mut self, x: i32, y: i32) -> Option<&mut T> {
+        let offset = Self::offset_in_chunk(x, y);
+        Some(&mut self.chunk_mut(Self::chunk_of(Point::new(x, y)))[offset])
+    }
+    fn get_ix_mut(&mut self, ix: usize) -> Option<&mut T> {
+        self.get_point_mut(self.ix_point(ix)?)
+    }
+    fn set_ix(&mut self, ix: usize, value: T) {
+        if let Some(p) = self.ix_point(ix) {
+            self.set_point(p, value);
+        }
+    }
+    /// Fills the region within the [bounding rectangle](Self::bounding_rect).
+    fn flood_fill(
+        &mut self,
+        start: Point,
+        neighborhood: Neighborhood,
+        predicate: impl Fn(&T) -> bool,
+        value: T,
+    ) -> usize {
+        let origin = self.origin();
+        let region = components::region(
+            self.width(),
+            self.height(),
+            start - origin,
+            neighborhood,
+            |p| predicate(self.get_point(p + origin).unwrap()),
+        );
+        for p in region.iter() {
+            self.set_point(*p + origin, value.clone());
+        }
+        region.len()
+    }
+    /// Labels the components within the [bounding rectangle](Self::bounding_rect). The grid of
+    /// labels is indexed from its lower corner, while the components are given in grid
+    /// coordinates.
+    fn label_components(
+        &self,
+        neighborhood: Neighborhood,
+    ) -> (SimpleValueGrid<u32>, Vec<ComponentInfo>)
+    where
+        T: PartialEq,
+    {
+        let origin = self.origin();
+        let (labels, mut components) =
+            components::label(self.width(), self.height(), neighborhood, |p| {
+                self.get_point(p + origin).unwrap()
+            });
+        for c in components.iter_mut() {
+            c.bounds = translate_rect(c.bounds, origin);
+            c.representative = c.representative + origin;
+        }
+        (labels, components)
+    }
+    /// Iterates mutably over the cells of the allocated chunks, chunk by chunk in no particular
+    /// order.
+    fn iter_mut<'a>(&'a mut self) -> impl Iterator<Item = &'a mut T>
+    where
+        T: 'a,
+    {
+        self.chunks.values_mut().flatten()
+    }
+    /// Iterates over the cells of the [bounding rectangle](Self::bounding_rect) in row-major
+    /// order, including unallocated cells within it.
+    fn cells<'a>(&'a self) -> impl Iterator<Item = (Point, &'a T)>
+    where
+        T: 'a,
+    {
+        let rect = self.bounding_rect();
+        let (x1, x2) = rect.map_or((0, -1), |r| (r.x1, r.x2));
+        let (y1, y2) = rect.map_or((0, -1), |r| (r.y1, r.y2));
+        (y1..=y2)
+            .flat_map(move |y| (x1..=x2).map(move |x| (Point::new(x, y), self.get(x, y).unwrap())))
+    }
+    /// Iterates over the rows of the [bounding rectangle](Self::bounding_rect), starting at the
+    /// lowest `y`.
+    fn rows<'a>(&'a self) -> impl Iterator<Item = impl Iterator<Item = &'a T>>
+    where
+        T: 'a,
+    {
+        let rect = self.bounding_rect();
+        let (x1, x2) = rect.map_or((0, -1), |r| (r.x1, r.x2));
+        let (y1, y2) = rect.map_or((0, -1), |r| (r.y1, r.y2));
+        (y1..=y2).map(move |y| (x1..=x2).map(move |x| self.get(x, y).unwrap()))
+    }
+    /// Iterates over the columns of the [bounding rectangle](Self::bounding_rect), starting at
+    /// the lowest `x`.
+    fn columns<'a>(&'a self) -> impl Iterator<Item = impl Iterator<Item = &'a T>>
+    where
+        T: 'a,
+    {
+        let rect = self.bounding_rect();
+        let (x1, x2) = rect.map_or((0, -1), |r| (r.x1, r.x2));
+        let (y1, y2) = rect.map_or((0, -1), |r| (r.y1, r.y2));
+        (x1..=x2).map(move |x| (y1..=y2).map(move |y| self.get(x, y).unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pathfinding::Passable;
+
+    #[test]
+    fn test_negative_coordinates() {
+        let mut grid = ChunkedGrid::empty(0);
+        assert_eq!(grid.get(-1000, 5), Some(&0));
+        assert_eq!(grid.chunk_count(), 0);
+        grid.set(-1, -1, 3);
+        grid.set(0, 0, 4);
+        grid.set(-33, 40, 5);
+        assert_eq!(grid.get(-1, -1), Some(&3));
+        assert_eq!(grid.get(0, 0), Some(&4));
+        assert_eq!(grid.get(-33, 40), Some(&5));
+        assert_eq!(grid.get(-2, -1), Some(&0));
+        assert_eq!(grid.chunk_count(), 3);
+        assert_eq!(
+            ChunkedGrid::<i32>::chunk_of(Point::new(-33, 40)),
+            Point::new(-2, 1)
+        );
+        assert_eq!(
+            grid.bounding_rect(),
+            Some(Rect {
+                x1: -64,
+                y1: -32,
+                x2: 31,
+                y2: 63
+            })
+        );
+        assert_eq!((grid.width(), grid.height()), (96, 96));
+        assert_eq!(grid.cells().count(), 96 * 96);
+        assert_eq!(grid.iter().sum::<i32>(), 12);
+        assert_eq!(
+            grid.cells().find(|(_, v)| **v == 5).unwrap().0,
+            Point::new(-33, 40)
+        );
+        assert_eq!(grid.compute_ix(-64, -32), 0);
+        let ix = grid.compute_ix(-33, 40);
+        assert_eq!(ix, 31 + 72 * 96);
+        assert_eq!(grid.get_ix(ix), Some(&5));
+        grid.set_ix(ix + 1, 6);
+        assert_eq!(grid.get(-32, 40), Some(&6));
+        assert_eq!(grid.get_ix(96 * 96), None);
+    }
+
+    #[test]
+    fn test_extreme_coordinates() {
+        let mut grid = ChunkedGrid::empty(false);
+        grid.set(i32::MAX, 0, true);
+        assert_eq!(
+            ChunkedGrid::<bool>::chunk_rect(ChunkedGrid::<bool>::chunk_of(Point::new(
+                i32::MAX,
+                i32::MAX
+            ))),
+            Rect {
+                x1: i32::MAX - 31,
+                y1: i32::MAX - 31,
+                x2: i32::MAX,
+                y2: i32::MAX
+            }
+        );
+        grid.set(i32::MIN, i32::MIN, true);
+        grid.set(i32::MAX, i32::MAX, true);
+        let full = Rect {
+            x1: i32::MIN,
+            y1: i32::MIN,
+            x2: i32::MAX,
+            y2: i32::MAX,
+        };
+        assert_eq!(grid.bounding_rect(), Some(full));
+        assert_eq!(grid.rect(), full);
+        assert_eq!((grid.width(), grid.height()), (1 << 32, 1 << 32));
+        assert!(grid.in_bounds_and_passable(Point::new(i32::MAX, 0)));
+        assert!(!grid.in_bounds_and_passable(Point::new(i32::MAX, 1)));
+        let ix = grid.compute_ix(i32::MAX, 0);
+        assert_eq!(grid.get_ix(ix), Some(&true));
+        assert_eq!(
+            grid.get_ix(grid.compute_ix(i32::MIN, i32::MIN)),
+            Some(&true)
+        );
+        grid.remove_chunk(Point::new(-(1 << 26), -(1 << 26)));
+        assert_eq!(
+            grid.rect(),
+            Rect {
+                x1: i32::MAX - 31,
+                y1: 0,
+                x2: i32::MAX,
+                y2: i32::MAX
+            }
+        );
+        assert_eq!(grid.width(), 32);
+    }
+
+    #[test]
+    fn test_drop_chunks() {
+        let mut grid = ChunkedGrid::new(40, 10, false);
+        assert_eq!(grid.chunk_count(), 2);
+        grid.set(100, -100, true);
+        let marked: Vec<Point> = grid
+            .chunks()
+            .filter(|(_, values)| values.iter().any(|v| *v))
+            .map(|(chunk, _)| chunk)
+            .collect();
+        assert_eq!(marked, vec![Point::new(3, -4)]);
+        grid.retain_chunks(|_, values| values.iter().any(|v| *v));
+        assert_eq!(grid.chunk_count(), 1);
+        assert!(grid.remove_chunk(Point::new(3, -4)).is_some());
+        assert_eq!(grid.get(100, -100), Some(&false));
+        assert_eq!(grid.bounding_rect(), None);
+        assert_eq!(grid.rows().count(), 0);
+    }
+
+    #[test]
+    fn test_negative_flood_fill() {
+        let mut grid = ChunkedGrid::empty(0);
+        grid.set_rect(Rect::new(-5, -32, 0, 31), 1);
+        assert_eq!(grid.rect(), Rect::new(-32, -32, 32, 32));
+        let filled = grid.flood_fill(
+            Point::new(-10, -10),
+            Neighborhood::VonNeumann,
+            |v| *v == 0,
+            2,
+        );
+        assert_eq!(filled, 27 * 32);
+        assert_eq!(grid.get(-32, -1), Some(&2));
+        assert_eq!(grid.get(-4, -1), Some(&0));
+        let (labels, components) = grid.label_components(Neighborhood::VonNeumann);
+        assert_eq!(components.len(), 3);
+        assert_eq!(components[1].representative, Point::new(-5, -32));
+        assert_eq!(
+            components[1].bounds,
+            Rect {
+                x1: -5,
+                y1: -32,
+                x2: -5,
+                y2: -1
+            }
+        );
+        assert_eq!(labels.values[27], 1);
+    }
+}
@@ -0,0 +1,393 @@
+//! Compact serde representations of grids and [RON](ron) helpers using them.
+//!
+//! The derived implementations of [Serialize] and [Deserialize] write the values of a grid as one
+//! long list. The modules here are opt-in alternatives for use with `#[serde(with = "...")]`:
+//!
+//! - [rle] writes the values of a [SimpleGrid] or [SimpleValueGrid] as runs of equal values.
+//! - [hex] writes the cells of a [BoolGrid] as a hexadecimal string.
+//! - [palette] writes a [SimpleGrid] or [SimpleValueGrid] with few distinct values as a palette
+//!   and a string per row, with the top row first like [ascii](crate::ascii).
+//!
+//! ```
+//! use grid_util::compact;
+//! use grid_util::grid::{BoolGrid, SimpleGrid};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Level {
+//!     #[serde(with = "compact::rle")]
+//!     heights: SimpleGrid<u8>,
+//!     #[serde(with = "compact::hex")]
+//!     walls: BoolGrid,
+//! }
+//! ```
+
+use crate::error::GridError;
+use crate::grid::{BoolGrid, SimpleGrid, SimpleValueGrid};
+use crate::validate::check_size;
+use serde::de::{DeserializeOwned, Error as _};
+use serde::ser::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Grids storing their values as a row-major list, which can be written by [rle] and [palette].
+pub trait RowMajor<T>: Sized {
+    fn dimensions(&self) -> (usize, usize);
+    fn values(&self) -> &[T];
+    fn from_row_major(width: usize, height: usize, values: Vec<T>) -> Result<Self, GridError>;
+}
+
+impl<T> RowMajor<T> for SimpleGrid<T> {
+    fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+    fn values(&self) -> &[T] {
+        &self.values
+    }
+    fn from_row_major(width: usize, height: usize, values: Vec<T>) -> Result<Self, GridError> {
+        SimpleGrid::from_values(width, height, values)
+    }
+}
+
+impl<T: Clone + Copy> RowMajor<T> for SimpleValueGrid<T> {
+    fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+    fn values(&self) -> &[T] {
+        &self.values
+    }
+    fn from_row_major(width: usize, height: usize, values: Vec<T>) -> Result<Self, GridError> {
+        SimpleValueGrid::from_values(width, height, values)
+    }
+}
+
+/// Run-length encoding of the values of a [RowMajor] grid, as a list of `(count, value)` pairs.
+pub mod rle {
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename = "Grid")]
+    struct RunLengths<V> {
+        width: usize,
+        height: usize,
+        runs: Vec<(usize, V)>,
+    }
+
+    pub fn serialize<G, T, S>(grid: &G, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        G: RowMajor<T>,
+        T: Serialize + PartialEq,
+        S: Serializer,
+    {
+        let (width, height) = grid.dimensions();
+        let mut runs: Vec<(usize, &T)> = Vec::new();
+        for v in grid.values() {
+            match runs.last_mut() {
+                Some((count, last)) if *last == v => *count += 1,
+                _ => runs.push((1, v)),
+            }
+        }
+        RunLengths {
+            width,
+            height,
+            runs,
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, G, T, D>(deserializer: D) -> Result<G, D::Error>
+    where
+        G: RowMajor<T>,
+        T: Deserialize<'de> + Clone,
+        D: Deserializer<'de>,
+    {
+        let encoded = RunLengths::<T>::deserialize(deserializer)?;
+        let cells = check_size(encoded.width, encoded.height).map_err(D::Error::custom)?;
+        let len = encoded
+            .runs
+            .iter()
+            .try_fold(0usize, |len, (count, _)| len.checked_add(*count))
+            .unwrap_or(usize::MAX);
+        if len != cells {
+            return Err(D::Error::custom(GridError::InvalidDimensions {
+                width: encoded.width,
+                height: encoded.height,
+                len,
+            }));
+        }
+        let values = encoded
+            .runs
+            .into_iter()
+            .flat_map(|(count, v)| std::iter::repeat_n(v, count))
+            .collect();
+        G::from_row_major(encoded.width, encoded.height, values).map_err(D::Error::custom)
+    }
+}
+
+/// Encoding of the cells of a [BoolGrid] as a hexadecimal string of bytes, with cell `ix` being
+/// bit `ix % 8` of byte `ix / 8`.
+pub mod hex {
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename = "BoolGrid")]
+    struct HexBits {
+        width: usize,
+        height: usize,
+        bits: String,
+    }
+
+    pub fn serialize<S: Serializer>(grid: &BoolGrid, serializer: S) -> Result<S::Ok, S::Error> {
+        let len = (grid.width * grid.height).div_ceil(8);
+        let bits = grid
+            .values
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .take(len)
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        HexBits {
+            width: grid.width,
+            height: grid.height,
+            bits,
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BoolGrid, D::Error> {
+        let encoded = HexBits::deserialize(deserializer)?;
+        let cells = check_size(encoded.width, encoded.height).map_err(D::Error::custom)?;
+        if encoded.bits.len() != 2 * cells.div_ceil(8) {
+            return Err(D::Error::custom(format!(
+                "{} hexadecimal digits do not fit a {}x{} grid",
+                encoded.bits.len(),
+                encoded.width,
+                encoded.height
+            )));
+        }
+        let mut grid = BoolGrid::new(encoded.width, encoded.height, false);
+        for (i, pair) in encoded.bits.as_bytes().chunks(2).enumerate() {
+            let byte = std::str::from_utf8(pair)
+                .ok()
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                .ok_or_else(|| D::Error::custom("invalid hexadecimal digits"))?;
+            grid.values[i / 8] |= (byte as u64) << (8 * (i % 8));
+        }
+        if grid.has_padding_set() {
+            return Err(D::Error::custom(GridError::PaddingSet {
+                width: grid.width,
+                height: grid.height,
+            }));
+        }
+        Ok(grid)
+    }
+}
+
+/// Encoding of a [RowMajor] grid with at most 62 distinct values as a palette of the values and
+/// a string per row, in which the characters `0-9`, `a-z` and `A-Z` stand for the values of the
+/// palette in order. The rows are listed with the top row first.
+pub mod palette {
+    use super::*;
+
+    const SYMBOLS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename = "Grid")]
+    struct Palette<V> {
+        width: usize,
+        height: usize,
+        palette: Vec<V>,
+        rows: Vec<String>,
+    }
+
+    pub fn serialize<G, T, S>(grid: &G, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        G: RowMajor<T>,
+        T: Serialize + PartialEq,
+        S: Serializer,
+    {
+        let (width, height) = grid.dimensions();
+        let mut palette: Vec<&T> = Vec::new();
+        let mut symbols = Vec::with_capacity(width * height);
+        for v in grid.values() {
+            let ix = match palette.iter().position(|p| *p == v) {
+                Some(ix) => ix,
+                None => {
+                    palette.push(v);
+                    palette.len() - 1
+                }
+            };
+            let symbol = SYMBOLS.get(ix).ok_or_else(|| {
+                S::Error::custom("more than 62 distinct values do not fit a palette")
+            })?;
+            symbols.push(*symbol as char);
+        }
+        let rows = (0..height)
+            .rev()
+            .map(|y| symbols[y * width..(y + 1) * width].iter().collect())
+            .collect();
+        Palette {
+            width,
+            height,
+            palette,
+            rows,
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, G, T, D>(deserializer: D) -> Result<G, D::Error>
+    where
+        G: RowMajor<T>,
+        T: Deserialize<'de> + Clone,
+        D: Deserializer<'de>,
+    {
+        let encoded = Palette::<T>::deserialize(deserializer)?;
+        let (width, height) = (encoded.width, encoded.height);
+        check_size(width, height).map_err(D::Error::custom)?;
+        if encoded.rows.len() != height {
+            return Err(D::Error::custom(GridError::InvalidDimensions {
+                width,
+                height,
+                len: encoded.rows.len() * width,
+            }));
+        }
+        for (i, row) in encoded.rows.iter().enumerate() {
+            let found = row.chars().count();
+            if found != width {
+                return Err(D::Error::custom(GridError::RaggedLine {
+                    line: i + 1,
+                    expected: width,
+                    found,
+                }));
+            }
+        }
+        let mut values = Vec::with_capacity(width * height);
+        for (y, row) in encoded.rows.iter().rev().enumerate() {
+            for (x, symbol) in row.chars().enumerate() {
+                let v = SYMBOLS
+                    .iter()
+                    .position(|s| *s as char == symbol)
+                    .and_then(|ix| encoded.palette.get(ix))
+                    .ok_or(GridError::InvalidSymbol {
+                        symbol,
+                        x: x as i32,
+                        y: y as i32,
+                    })
+                    .map_err(D::Error::custom)?;
+                values.push(v.clone());
+            }
+        }
+        G::from_row_major(width, height, values).map_err(D::Error::custom)
+    }
+}
+
+/// Writes pretty RON using the given serialization function.
+fn to_ron_with<S>(serialize: S) -> Result<String, ron::Error>
+where
+    S: FnOnce(&mut ron::Serializer<&mut String>) -> Result<(), ron::Error>,
+{
+    let mut text = String::new();
+    let mut serializer = ron::Serializer::new(&mut text, Some(ron::ser::PrettyConfig::default()))?;
+    serialize(&mut serializer)?;
+    Ok(text)
+}
+
+/// Reads RON using the given deserialization function, rejecting trailing characters.
+fn from_ron_with<'de, G>(
+    text: &'de str,
+    deserialize: impl FnOnce(&mut ron::Deserializer<'de>) -> Result<G, ron::Error>,
+) -> Result<G, ron::error::SpannedError> {
+    let mut deserializer = ron::Deserializer::from_str(text)?;
+    let grid = deserialize(&mut deserializer).map_err(|e| deserializer.span_error(e))?;
+    deserializer.end().map_err(|e| deserializer.span_error(e))?;
+    Ok(grid)
+}
+
+impl<T: Serialize + DeserializeOwned + PartialEq + Clone> SimpleGrid<T> {
+    /// Writes the grid as pretty RON in the [rle] representation.
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        to_ron_with(|s| rle::serialize(self, s))
+    }
+    /// Reads a grid written by [to_ron](Self::to_ron).
+    pub fn from_ron(text: &str) -> Result<Self, ron::error::SpannedError> {
+        from_ron_with(text, |d| rle::deserialize(d))
+    }
+}
+
+impl<T: Serialize + DeserializeOwned + PartialEq + Clone + Copy> SimpleValueGrid<T> {
+    /// Writes the grid as pretty RON in the [rle] representation.
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        to_ron_with(|s| rle::serialize(self, s))
+    }
+    /// Reads a grid written by [to_ron](Self::to_ron).
+    pub fn from_ron(text: &str) -> Result<Self, ron::error::SpannedError> {
+        from_ron_with(text, |d| rle::deserialize(d))
+    }
+}
+
+impl BoolGrid {
+    /// Writes the grid as pretty RON in the [hex] representation.
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        to_ron_with(|s| hex::serialize(self, s))
+    }
+    /// Reads a grid written by [to_ron](Self::to_ron).
+    pub fn from_ron(text: &str) -> Result<Self, ron::error::SpannedError> {
+        from_ron_with(text, |d| hex::deserialize(d))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{Grid, GridWrite, ValueGrid};
+    use crate::rect::Rect;
+
+    #[test]
+    fn test_ron_round_trip() {
+        let mut grid = SimpleGrid::new(40, 30, 0u8);
+        grid.set_rect(Rect::new(5, 5, 10, 3), 7);
+        let text = grid.to_ron().unwrap();
+        assert!(text.len() < ron::to_string(&grid).unwrap().len() / 10);
+        assert_eq!(
+            SimpleGrid::<u8>::from_ron(&text).unwrap().values,
+            grid.values
+        );
+
+        let mut bools = BoolGrid::new(67, 3, false);
+        bools.set(66, 2, true);
+        bools.set(0, 0, true);
+        let text = bools.to_ron().unwrap();
+        assert_eq!(BoolGrid::from_ron(&text).unwrap(), bools);
+
+        let values = SimpleValueGrid::from_values(2, 2, vec![1.5f32, 1.5, 2.0, 1.5]).unwrap();
+        let read = SimpleValueGrid::<f32>::from_ron(&values.to_ron().unwrap()).unwrap();
+        assert_eq!(read.values, values.values);
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Level {
+        #[serde(with = "palette")]
+        tiles: SimpleGrid<char>,
+        #[serde(with = "hex")]
+        walls: BoolGrid,
+    }
+
+    #[test]
+    fn test_palette() {
+        let tiles = SimpleGrid::from_ascii("~~#\n..#", |c| c).unwrap();
+        let level = Level {
+            walls: BoolGrid::from_ascii("~~#\n..#", |c| c == '#').unwrap(),
+            tiles,
+        };
+        let text = ron::to_string(&level).unwrap();
+        assert!(text.contains(r#"palette:['.','#','~'],rows:["221","001"]"#));
+        assert!(text.contains(r#"bits:"24""#));
+        let read: Level = ron::from_str(&text).unwrap();
+        assert_eq!(read.tiles.values, level.tiles.values);
+        assert_eq!(read.walls, level.walls);
+
+        let ragged = text.replace("\"001\"", "\"01\"");
+        assert!(ron::from_str::<Level>(&ragged).is_err());
+        let padded = text.replace("bits:\"24\"", "bits:\"e4\"");
+        assert!(ron::from_str::<Level>(&padded).is_err());
+    }
+}
@@ -0,0 +1,173 @@
+//! Flood fill and connected-component labelling shared by the [Grid](crate::grid::Grid) and
+//! [ValueGrid] traits.
+
+use crate::grid::{BoolGrid, GridDims, SimpleValueGrid, ValueGrid};
+use crate::point::{Neighborhood, Point};
+use crate::rect::Rect;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Summary of a connected component found by `label_components`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ComponentInfo {
+    /// The label of the cells of the component in the label grid.
+    pub label: u32,
+    /// The number of cells in the component.
+    pub size: usize,
+    /// The smallest rectangle containing the component (see [Rect::contains]).
+    pub bounds: Rect,
+    /// The first cell of the component in row-major order.
+    pub representative: Point,
+}
+
+/// Collects the cells connected to `start` for which `inside` holds, in breadth-first order.
+pub(crate) fn region(
+    width: usize,
+    height: usize,
+    start: Point,
+    neighborhood: Neighborhood,
+    inside: impl Fn(Point) -> bool,
+) -> Vec<Point> {
+    let mut visited = BoolGrid::new(width, height, false);
+    if !visited.point_in_bounds(start) || !inside(start) {
+        return vec![];
+    }
+    visited.set_point(start, true);
+    let mut cells = vec![start];
+    let mut next = 0;
+    while next < cells.len() {
+        let p = cells[next];
+        next += 1;
+        for dir in neighborhood.directions() {
+            let n = p + *dir;
+            if visited.point_in_bounds(n) && !visited.get_point(n) && inside(n) {
+                visited.set_point(n, true);
+                cells.push(n);
+            }
+        }
+    }
+    cells
+}
+
+/// Labels the connected components of cells with equal values. Labels are assigned in
+/// row-major order of the components' first cells, starting at 0.
+pub(crate) fn label<V: PartialEq>(
+    width: usize,
+    height: usize,
+    neighborhood: Neighborhood,
+    get: impl Fn(Point) -> V,
+) -> (SimpleValueGrid<u32>, Vec<ComponentInfo>) {
+    let mut labels = SimpleValueGrid {
+        width,
+        height,
+        values: vec![u32::MAX; width * height],
+    };
+    let mut components = vec![];
+    let mut queue = VecDeque::new();
+    for y in 0..height as i32 {
+        for x in 0..width as i32 {
+            let start = Point::new(x, y);
+            if labels.get_point(start) != u32::MAX {
+                continue;
+            }
+            let label = components.len() as u32;
+            let value = get(start);
+            let mut info = ComponentInfo {
+                label,
+                size: 0,
+                bounds: Rect {
+                    x1: x,
+                    x2: x,
+                    y1: y,
+                    y2: y,
+                },
+                representative: start,
+            };
+            labels.set_point(start, label);
+            queue.push_back(start);
+            while let Some(p) = queue.pop_front() {
+                info.size += 1;
+                info.bounds.x1 = info.bounds.x1.min(p.x);
+                info.bounds.x2 = info.bounds.x2.max(p.x);
+                info.bounds.y1 = info.bounds.y1.min(p.y);
+                info.bounds.y2 = info.bounds.y2.max(p.y);
+                for dir in neighborhood.directions() {
+                    let n = p + *dir;
+                    if labels.point_in_bounds(n)
+                        && labels.get_point(n) == u32::MAX
+                        && get(n) == value
+                    {
+                        labels.set_point(n, label);
+                        queue.push_back(n);
+                    }
+                }
+            }
+            components.push(info);
+        }
+    }
+    (labels, components)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::{BoolGrid, Grid, GridWrite, SimpleGrid, ValueGrid};
+    use crate::point::{Neighborhood, Point};
+    use crate::rect::Rect;
+
+    fn caves() -> BoolGrid {
+        // Two open areas touching only diagonally at (2,2)-(3,3), plus a single cell.
+        let mut grid = BoolGrid::new(6, 6, false);
+        grid.set_rect(Rect::new(0, 0, 2, 2), true);
+        grid.set_rect(Rect::new(3, 3, 1, 2), true);
+        grid.set(5, 0, true);
+        grid
+    }
+
+    #[test]
+    fn test_label_components() {
+        let grid = caves();
+        let (labels, components) = grid.label_components(Neighborhood::VonNeumann);
+        let open: Vec<_> = components
+            .iter()
+            .filter(|c| grid.get_point(c.representative))
+            .collect();
+        assert_eq!(open.len(), 3);
+        let largest = open.iter().max_by_key(|c| c.size).unwrap();
+        assert_eq!(largest.size, 9);
+        assert_eq!(largest.bounds, Rect::new(0, 0, 2, 2));
+        assert_eq!(labels.get(1, 1), largest.label);
+        let (_, components) = grid.label_components(Neighborhood::Moore);
+        let sizes: Vec<usize> = components
+            .iter()
+            .filter(|c| grid.get_point(c.representative))
+            .map(|c| c.size)
+            .collect();
+        assert_eq!(sizes, vec![15, 1]);
+        assert_eq!(components.iter().map(|c| c.size).sum::<usize>(), 36);
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        let mut grid = caves();
+        assert_eq!(
+            grid.flood_fill(Point::new(0, 0), Neighborhood::VonNeumann, |v| v, false),
+            9
+        );
+        assert_eq!(grid.count_ones(), 7);
+        let mut tiles = SimpleGrid::new(4, 3, '.');
+        tiles.set_rect(Rect::new(2, 0, 0, 2), '#');
+        let filled = tiles.flood_fill(Point::new(0, 0), Neighborhood::Moore, |c| *c == '.', '~');
+        assert_eq!(filled, 6);
+        assert_eq!(tiles.get(3, 0), Some(&'.'));
+        // Filling with a value that still matches the predicate terminates.
+        assert_eq!(
+            tiles.flood_fill(
+                Point::new(3, 0),
+                Neighborhood::VonNeumann,
+                |c| *c != '#',
+                '.'
+            ),
+            3
+        );
+    }
+}
@@ -0,0 +1,274 @@
+//! "Dijkstra maps": grids of distances to the nearest of a set of goals, computed using
+//! multi-source [breadth-first search](bfs_map) or [Dijkstra's algorithm](dijkstra_map).
+//!
+//! Moves never cut corners, like in [pathfinding](crate::pathfinding).
+
+use crate::grid::{GridDims, GridRead, SimpleValueGrid, ValueGrid};
+use crate::pathfinding::{successors, Local, OpenNode, Passable};
+use crate::point::{Neighborhood, Point};
+use crate::rect::Rect;
+use std::collections::{BinaryHeap, VecDeque};
+
+/// Distance type of a Dijkstra map, with a value marking unreached cells.
+pub trait Distance: Clone + Copy + PartialOrd {
+    const UNREACHED: Self;
+}
+
+impl Distance for u32 {
+    const UNREACHED: u32 = u32::MAX;
+}
+
+impl Distance for f32 {
+    const UNREACHED: f32 = f32::INFINITY;
+}
+
+/// Computes the number of moves from every cell to the nearest goal. Cells that are impassable,
+/// unreachable or further than `max_radius` moves are [u32::MAX].
+///
+/// The map is indexed from the lower corner of the [rect](crate::grid::GridDims::rect) of the
+/// passability grid, which is the origin for all but offset grids.
+pub fn bfs_map<P: Passable>(
+    passable: &P,
+    goals: &[Point],
+    neighborhood: Neighborhood,
+    max_radius: Option<u32>,
+) -> SimpleValueGrid<u32> {
+    let local = Local::new(passable);
+    let goals: Vec<Point> = goals.iter().map(|g| *g - local.origin).collect();
+    bfs_map_local(&local, &goals, neighborhood, max_radius)
+}
+
+/// [bfs_map] on a grid whose cells are indexed from the origin.
+fn bfs_map_local<P: Passable>(
+    passable: &P,
+    goals: &[Point],
+    neighborhood: Neighborhood,
+    max_radius: Option<u32>,
+) -> SimpleValueGrid<u32> {
+    let mut map = SimpleValueGrid::new(passable.width(), passable.height(), u32::UNREACHED);
+    let mut queue = VecDeque::new();
+    for goal in goals {
+        if passable.in_bounds_and_passable(*goal) && map.get_point(*goal) != 0 {
+            map.set_point(*goal, 0);
+            queue.push_back(*goal);
+        }
+    }
+    while let Some(p) = queue.pop_front() {
+        let distance = map.get_point(p) + 1;
+        if max_radius.is_some_and(|r| distance > r) {
+            continue;
+        }
+        for (next, _) in successors(passable, p, neighborhood) {
+            if map.get_point(next) == u32::UNREACHED {
+                map.set_point(next, distance);
+                queue.push_back(next);
+            }
+        }
+    }
+    map
+}
+
+/// Computes the cost of the cheapest path from every cell to the nearest goal, where entering a
+/// cell costs its value in `costs` times [SQRT_2](std::f32::consts::SQRT_2) for diagonal moves.
+/// Cells with a negative or non-finite cost are impassable. Cells that are impassable,
+/// unreachable or further than `max_radius` are infinite.
+///
+/// Like that of [bfs_map], the map is indexed from the lower corner of the
+/// [rect](crate::grid::GridDims::rect) of `costs`.
+pub fn dijkstra_map<C: GridRead<f32>>(
+    costs: &C,
+    goals: &[Point],
+    neighborhood: Neighborhood,
+    max_radius: Option<f32>,
+) -> SimpleValueGrid<f32> {
+    let passable = Costs(costs);
+    let local = Local::new(&passable);
+    let origin = local.origin;
+    let cost = |p: Point| costs.read_point(p + origin).unwrap_or(f32::INFINITY);
+    let mut map = SimpleValueGrid::new(local.width(), local.height(), f32::UNREACHED);
+    let mut open = BinaryHeap::new();
+    let mut seq = 0;
+    for goal in goals {
+        let goal = *goal - origin;
+        if local.in_bounds_and_passable(goal) {
+            map.set_point(goal, 0.0);
+            seq += 1;
+            open.push(OpenNode {
+                f: 0.0,
+                h: 0.0,
+                seq,
+                ix: map.get_ix_point(&goal),
+            });
+        }
+    }
+    while let Some(node) = open.pop() {
+        if node.f > map.values[node.ix] {
+            continue;
+        }
+        let p = Point::new((node.ix % map.width) as i32, (node.ix / map.width) as i32);
+        // Walking towards the goal, `next` steps into `p`.
+        let entered = cost(p);
+        for (next, step) in successors(&local, p, neighborhood) {
+            let distance = node.f + step * entered;
+            let next_ix = map.get_ix_point(&next);
+            if distance < map.values[next_ix] && max_radius.is_none_or(|r| distance <= r) {
+                map.values[next_ix] = distance;
+                seq += 1;
+                open.push(OpenNode {
+                    f: distance,
+                    h: 0.0,
+                    seq,
+                    ix: next_ix,
+                });
+            }
+        }
+    }
+    map
+}
+
+/// Passability of a grid of costs for [dijkstra_map].
+struct Costs<'a, C>(&'a C);
+
+impl<C: GridDims> GridDims for Costs<'_, C> {
+    fn width(&self) -> usize {
+        self.0.width()
+    }
+    fn height(&self) -> usize {
+        self.0.height()
+    }
+    fn rect(&self) -> Rect {
+        self.0.rect()
+    }
+}
+
+impl<C: GridRead<f32>> Passable for Costs<'_, C> {
+    fn is_passable(&self, point: Point) -> bool {
+        self.0
+            .read_point(point)
+            .is_some_and(|cost| cost.is_finite() && cost >= 0.0)
+    }
+}
+
+/// Follows a Dijkstra map downhill from `from`, each step moving to the neighbour with the
+/// lowest distance, until reaching a goal. Ties are broken in [Direction](crate::Direction)
+/// order. Diagonal steps are only taken when both adjacent cells have been reached, which keeps
+/// the walk from cutting corners. The walk is just `from` if it has not been reached.
+pub fn walk_downhill<D: Distance>(
+    map: &SimpleValueGrid<D>,
+    from: Point,
+    neighborhood: Neighborhood,
+) -> Vec<Point> {
+    let reached = |p: Point| map.point_in_bounds(p) && map.get_point(p) != D::UNREACHED;
+    let mut path = vec![from];
+    if !reached(from) {
+        return path;
+    }
+    let mut current = from;
+    loop {
+        let mut best = current;
+        for dir in neighborhood.directions() {
+            let next = current + *dir;
+            let allowed = reached(next)
+                && (!dir.diagonal()
+                    || (reached(Point::new(next.x, current.y))
+                        && reached(Point::new(current.x, next.y))));
+            if allowed && map.get_point(next) < map.get_point(best) {
+                best = next;
+            }
+        }
+        if best == current {
+            return path;
+        }
+        path.push(best);
+        current = best;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::GridWrite;
+    use crate::offset::OffsetGrid;
+    use crate::pathfinding::walled;
+    use crate::rect::Rect;
+
+    #[test]
+    fn test_bfs_map() {
+        let grid = walled();
+        let goals = [Point::new(0, 0), Point::new(6, 4)];
+        let map = bfs_map(&grid, &goals, Neighborhood::VonNeumann, None);
+        assert_eq!(map.get(0, 0), 0);
+        assert_eq!(map.get(2, 4), 4);
+        assert_eq!(map.get(6, 0), 4);
+        assert_eq!(map.get(3, 1), u32::MAX);
+        let near = bfs_map(&grid, &goals, Neighborhood::VonNeumann, Some(3));
+        assert_eq!(near.get(2, 1), 3);
+        assert_eq!(near.get(2, 4), u32::MAX);
+        let moore = bfs_map(&grid, &goals[..1], Neighborhood::Moore, None);
+        assert_eq!(moore.get(2, 2), 2);
+        // Going around the top of the wall without cutting its corner.
+        assert_eq!(moore.get(4, 3), 7);
+    }
+
+    #[test]
+    fn test_bfs_map_offset_grid() {
+        let origin = Point::new(100, 100);
+        let grid = OffsetGrid::at(origin, walled());
+        let map = bfs_map(&grid, &[origin], Neighborhood::VonNeumann, None);
+        let local = bfs_map(
+            &walled(),
+            &[Point::new(0, 0)],
+            Neighborhood::VonNeumann,
+            None,
+        );
+        assert_eq!(map.values, local.values);
+    }
+
+    #[test]
+    fn test_dijkstra_map_and_walk() {
+        let mut costs: SimpleValueGrid<f32> = ValueGrid::new(5, 3, 1.0);
+        // A swamp in the middle row that is cheaper to walk around.
+        costs.set_rect(Rect::new(1, 1, 2, 0), 5.0);
+        costs.set(4, 1, f32::INFINITY);
+        let map = dijkstra_map(&costs, &[Point::new(0, 1)], Neighborhood::VonNeumann, None);
+        assert_eq!(map.get(3, 1), 5.0);
+        assert_eq!(map.get(4, 1), f32::INFINITY);
+        let path = walk_downhill(&map, Point::new(3, 1), Neighborhood::VonNeumann);
+        assert_eq!(path.len(), 6);
+        assert_eq!(path.last(), Some(&Point::new(0, 1)));
+        assert_eq!(path[1], Point::new(3, 2));
+        let bounded = dijkstra_map(&costs, &[Point::new(0, 1)], Neighborhood::Moore, Some(2.0));
+        assert_eq!(bounded.get(1, 2), std::f32::consts::SQRT_2);
+        assert_eq!(bounded.get(2, 2), f32::INFINITY);
+        assert_eq!(
+            walk_downhill(&bounded, Point::new(4, 1), Neighborhood::Moore),
+            vec![Point::new(4, 1)]
+        );
+    }
+
+    #[test]
+    fn test_dijkstra_map_costs() {
+        let costs = SimpleValueGrid::from_values(3, 1, vec![10.0, 1.0, 1.0]).unwrap();
+        let map = dijkstra_map(&costs, &[Point::new(2, 0)], Neighborhood::VonNeumann, None);
+        assert_eq!(map.values, vec![2.0, 1.0, 0.0]);
+        // The goal closer in cost wins even where a costly goal cell is nearer in moves.
+        let costs = SimpleValueGrid::from_values(4, 1, vec![1.0, 1.0, 1.0, 9.0]).unwrap();
+        let goals = [Point::new(0, 0), Point::new(3, 0)];
+        let map = dijkstra_map(&costs, &goals, Neighborhood::VonNeumann, None);
+        assert_eq!(map.values, vec![0.0, 1.0, 2.0, 0.0]);
+        let offset = OffsetGrid::at(Point::new(-5, 7), costs.clone());
+        let goals = goals.map(|g| g + offset.origin);
+        let shifted = dijkstra_map(&offset, &goals, Neighborhood::VonNeumann, Some(1.0));
+        assert_eq!(shifted.values, vec![0.0, 1.0, f32::INFINITY, 0.0]);
+    }
+
+    #[test]
+    fn test_walk_downhill_bfs() {
+        let grid = walled();
+        let map = bfs_map(&grid, &[Point::new(0, 0)], Neighborhood::Moore, None);
+        let path = walk_downhill(&map, Point::new(6, 0), Neighborhood::Moore);
+        assert_eq!(path.len() as u32, map.get(6, 0) + 1);
+        assert_eq!(path.last(), Some(&Point::new(0, 0)));
+        assert!(path.contains(&Point::new(3, 4)));
+    }
+}
@@ -0,0 +1,102 @@
+//! [GridError] type returned by the fallible parts of the grid API.
+
+use std::error::Error;
+use std::fmt;
+
+/// Errors that can occur when accessing or constructing grids.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GridError {
+    /// The coordinate lies outside of a grid with the given dimensions.
+    OutOfBounds {
+        x: i32,
+        y: i32,
+        width: usize,
+        height: usize,
+    },
+    /// The number of values does not match the given dimensions.
+    InvalidDimensions {
+        width: usize,
+        height: usize,
+        len: usize,
+    },
+    /// The number of cells exceeds the limit for deserialized grids, see
+    /// [max_deserialized_cells](crate::validate::max_deserialized_cells).
+    TooLarge {
+        width: usize,
+        height: usize,
+        limit: usize,
+    },
+    /// The number of words of a [BoolGrid](crate::BoolGrid) does not match its dimensions.
+    InvalidWordCount {
+        width: usize,
+        height: usize,
+        words: usize,
+    },
+    /// Padding bits past the cells of a [BoolGrid](crate::BoolGrid) are set.
+    PaddingSet { width: usize, height: usize },
+    /// A line of an ASCII map differs in length from the first line, with lines numbered from 1.
+    RaggedLine {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// A character of an ASCII map at the given coordinate does not stand for a value.
+    InvalidSymbol { symbol: char, x: i32, y: i32 },
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GridError::OutOfBounds {
+                x,
+                y,
+                width,
+                height,
+            } => write!(
+                f,
+                "coordinate ({},{}) is out of bounds for a {}x{} grid",
+                x, y, width, height
+            ),
+            GridError::InvalidDimensions { width, height, len } => {
+                write!(f, "{} values do not fit a {}x{} grid", len, width, height)
+            }
+            GridError::TooLarge {
+                width,
+                height,
+                limit,
+            } => write!(
+                f,
+                "a {}x{} grid exceeds the limit of {} cells",
+                width, height, limit
+            ),
+            GridError::InvalidWordCount {
+                width,
+                height,
+                words,
+            } => write!(
+                f,
+                "{} words do not fit a {}x{} BoolGrid",
+                words, width, height
+            ),
+            GridError::PaddingSet { width, height } => write!(
+                f,
+                "bits past the cells of a {}x{} BoolGrid are set",
+                width, height
+            ),
+            GridError::RaggedLine {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {} has {} characters where {} were expected",
+                line, found, expected
+            ),
+            GridError::InvalidSymbol { symbol, x, y } => {
+                write!(f, "invalid symbol {:?} at ({},{})", symbol, x, y)
+            }
+        }
+    }
+}
+
+impl Error for GridError {}
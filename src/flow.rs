@@ -0,0 +1,239 @@
+//! [FlowField]'s giving every cell the [Direction] of its next step towards a goal.
+
+use crate::dijkstra::bfs_map;
+use crate::direction::Direction;
+use crate::grid::{BoolGrid, GridDims, SimpleValueGrid, ValueGrid};
+use crate::pathfinding::{successors, Local, Passable};
+use crate::point::{Neighborhood, Point};
+use crate::rect::Rect;
+use crate::resize::clip_rect;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// Grid of the [Direction] of the best next step from each cell towards a goal, which is
+/// [NONE](Direction::NONE) at the goal and in cells that cannot reach it. The steps follow the
+/// distances of a [bfs_map], ties being broken in [Direction] order, and never cut corners.
+///
+/// Points are taken in the coordinates of the passability grid, while the grids of
+/// [directions](Self::directions) and [distances](Self::distances) are indexed from the lower
+/// corner of its [rect](GridDims::rect), like those of [bfs_map].
+#[derive(Clone, Debug)]
+pub struct FlowField {
+    goal: Point,
+    origin: Point,
+    neighborhood: Neighborhood,
+    distances: SimpleValueGrid<u32>,
+    directions: SimpleValueGrid<Direction>,
+}
+
+impl FlowField {
+    /// Builds the flow field towards `goal` over the passability grid.
+    pub fn new<P: Passable>(passable: &P, goal: Point, neighborhood: Neighborhood) -> FlowField {
+        let local = Local::new(passable);
+        let distances = bfs_map(&local, &[goal - local.origin], neighborhood, None);
+        let mut field = FlowField {
+            goal,
+            origin: local.origin,
+            neighborhood,
+            directions: ValueGrid::new(distances.width, distances.height, Direction::NONE),
+            distances,
+        };
+        for y in 0..field.distances.height as i32 {
+            for x in 0..field.distances.width as i32 {
+                field.update_direction(&local, Point::new(x, y));
+            }
+        }
+        field
+    }
+    pub fn goal(&self) -> Point {
+        self.goal
+    }
+    /// The directions of the field.
+    pub fn directions(&self) -> &SimpleValueGrid<Direction> {
+        &self.directions
+    }
+    /// The number of moves from each cell to the goal, [u32::MAX] when it is unreachable.
+    pub fn distances(&self) -> &SimpleValueGrid<u32> {
+        &self.distances
+    }
+    /// The direction of the next step from a point, [NONE](Direction::NONE) when it is out of
+    /// bounds.
+    pub fn direction(&self, point: Point) -> Direction {
+        let point = point - self.origin;
+        if self.directions.point_in_bounds(point) {
+            self.directions.get_point(point)
+        } else {
+            Direction::NONE
+        }
+    }
+    /// Recomputes the direction of a cell from the distances of its neighbours, in the
+    /// coordinates of the grids of the field.
+    fn update_direction<P: Passable>(&mut self, passable: &P, point: Point) {
+        let distance = self.distances.get_point(point);
+        let mut best = (distance, Direction::NONE);
+        if distance != u32::MAX {
+            for (next, _) in successors(passable, point, self.neighborhood) {
+                let d = self.distances.get_point(next);
+                if d < best.0 {
+                    best = (d, point.dir_obj(&next));
+                }
+            }
+        }
+        self.directions.set_point(point, best.1);
+    }
+    /// Updates the field after the passability of the cells in `changed` (see [Rect::contains])
+    /// has changed. Only the cells whose route to the goal passed through or along the changed
+    /// cells, and the cells that get closer to the goal, are recomputed.
+    pub fn update<P: Passable>(&mut self, passable: &P, changed: Rect) {
+        let passable = &Local::new(passable);
+        let origin = passable.origin;
+        let goal = self.goal - origin;
+        let (width, height) = (self.distances.width, self.distances.height);
+        // Moves along the edge of the changed cells may now cut a corner, so include those.
+        let grown = Rect {
+            x1: changed.x1 - origin.x - 1,
+            y1: changed.y1 - origin.y - 1,
+            x2: changed.x2 - origin.x + 1,
+            y2: changed.y2 - origin.y + 1,
+        };
+        let Some(grown) = clip_rect(grown, width, height) else {
+            return;
+        };
+        // Collect the affected cells: those in the grown rectangle and those whose steps lead
+        // through an affected cell.
+        let mut affected = BoolGrid::new(width, height, false);
+        let mut stack = vec![];
+        for y in grown.y1..=grown.y2 {
+            for x in grown.x1..=grown.x2 {
+                affected.set(x, y, true);
+                stack.push(Point::new(x, y));
+            }
+        }
+        let mut invalidated = vec![];
+        while let Some(p) = stack.pop() {
+            invalidated.push(p);
+            for dir in self.neighborhood.directions() {
+                let q = p + *dir;
+                if affected.point_in_bounds(q)
+                    && !affected.get_point(q)
+                    && q + self.directions.get_point(q) == p
+                    && self.directions.get_point(q) != Direction::NONE
+                {
+                    affected.set_point(q, true);
+                    stack.push(q);
+                }
+            }
+        }
+        for p in invalidated.iter() {
+            self.distances.set_point(*p, u32::MAX);
+        }
+        // Seed the affected cells from their unaffected neighbours and the goal, then relax
+        // outwards, which also lowers distances that the change shortened.
+        let mut open = BinaryHeap::new();
+        for p in invalidated.iter() {
+            if !passable.in_bounds_and_passable(*p) {
+                continue;
+            }
+            let mut distance = if *p == goal { 0 } else { u32::MAX };
+            for (next, _) in successors(passable, *p, self.neighborhood) {
+                let d = self.distances.get_point(next);
+                if !affected.get_point(next) && d != u32::MAX {
+                    distance = distance.min(d + 1);
+                }
+            }
+            if distance != u32::MAX {
+                self.distances.set_point(*p, distance);
+                open.push(Reverse((distance, p.y, p.x)));
+            }
+        }
+        let mut lowered = vec![];
+        while let Some(Reverse((distance, y, x))) = open.pop() {
+            let p = Point::new(x, y);
+            if distance > self.distances.get_point(p) {
+                continue;
+            }
+            for (next, _) in successors(passable, p, self.neighborhood) {
+                if distance + 1 < self.distances.get_point(next) {
+                    self.distances.set_point(next, distance + 1);
+                    lowered.push(next);
+                    open.push(Reverse((distance + 1, next.y, next.x)));
+                }
+            }
+        }
+        // Directions depend on the distances of the neighbours, so refresh those too.
+        let mut refresh = BoolGrid::new(width, height, false);
+        for p in invalidated.into_iter().chain(lowered) {
+            refresh.set_point(p, true);
+            for dir in Neighborhood::Moore.directions() {
+                let q = p + *dir;
+                if refresh.point_in_bounds(q) {
+                    refresh.set_point(q, true);
+                }
+            }
+        }
+        for p in refresh.iter_ones() {
+            self.update_direction(passable, p);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::GridWrite;
+    use crate::offset::OffsetGrid;
+    use crate::pathfinding::walled;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_flow_field() {
+        let grid = walled();
+        let field = FlowField::new(&grid, Point::new(6, 0), Neighborhood::VonNeumann);
+        assert_eq!(field.direction(Point::new(6, 0)), Direction::NONE);
+        assert_eq!(field.direction(Point::new(3, 0)), Direction::NONE);
+        assert_eq!(field.direction(Point::new(0, 0)), Direction::NORTH);
+        assert_eq!(field.direction(Point::new(3, 4)), Direction::EAST);
+        assert_eq!(field.direction(Point::new(6, 3)), Direction::SOUTH);
+        let mut p = Point::new(0, 0);
+        let mut steps = 0;
+        while field.direction(p) != Direction::NONE {
+            p = p + field.direction(p);
+            steps += 1;
+        }
+        assert_eq!((p, steps), (Point::new(6, 0), field.distances().get(0, 0)));
+    }
+
+    #[test]
+    fn test_flow_field_offset_grid() {
+        let origin = Point::new(100, 100);
+        let grid = OffsetGrid::at(origin, walled());
+        let field = FlowField::new(&grid, origin + Point::new(6, 0), Neighborhood::VonNeumann);
+        assert_eq!(field.direction(origin), Direction::NORTH);
+        assert_eq!(field.direction(Point::new(0, 0)), Direction::NONE);
+        let local = FlowField::new(&walled(), Point::new(6, 0), Neighborhood::VonNeumann);
+        assert_eq!(field.directions().values, local.directions().values);
+    }
+
+    #[test]
+    fn test_incremental_update_matches_rebuild() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for neighborhood in [Neighborhood::VonNeumann, Neighborhood::Moore] {
+            let mut grid = BoolGrid::new(30, 20, true);
+            for _ in 0..150 {
+                grid.set(rng.gen_range(0..30), rng.gen_range(0..20), false);
+            }
+            let goal = Point::new(15, 10);
+            grid.set_point(goal, true);
+            let mut field = FlowField::new(&grid, goal, neighborhood);
+            for _ in 0..50 {
+                let rect = Rect::new(rng.gen_range(0..30), rng.gen_range(0..20), 2, 1);
+                grid.set_rect(rect, rng.gen_bool(0.5));
+                field.update(&grid, rect);
+                let rebuilt = FlowField::new(&grid, goal, neighborhood);
+                assert_eq!(field.distances().values, rebuilt.distances().values);
+                assert_eq!(field.directions().values, rebuilt.directions().values);
+            }
+        }
+    }
+}
@@ -0,0 +1,336 @@
+//! Field of view using symmetric shadowcasting over an opacity [BoolGrid], see [compute_fov],
+//! and matching [line of sight](has_line_of_sight) queries between two cells.
+//!
+//! The algorithm follows Albert Ford's "Symmetric Shadowcasting": walls are visible when any
+//! part of them is lit, floor cells only when their center is. This makes visibility between
+//! floor cells symmetric, so if `a` sees `b` then `b` sees `a`.
+
+use crate::grid::{BoolGrid, GridDims, ValueGrid};
+use crate::point::Point;
+use serde::{Deserialize, Serialize};
+
+/// Distance metric bounding the field of view.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Metric {
+    /// [Point::manhattan_distance], giving a diamond.
+    Manhattan,
+    /// [Point::move_distance], giving a square.
+    Chebyshev,
+    /// [Point::euclidean_distance], giving a disc.
+    Euclidean,
+}
+
+impl Metric {
+    /// Tests whether `b` is within `radius` of `a`.
+    pub fn within(&self, a: &Point, b: &Point, radius: u32) -> bool {
+        let radius = radius as i64;
+        match self {
+            Metric::Manhattan => a.manhattan_distance(b) as i64 <= radius,
+            Metric::Chebyshev => a.move_distance(b) as i64 <= radius,
+            Metric::Euclidean => {
+                let dx = (b.x - a.x) as i64;
+                let dy = (b.y - a.y) as i64;
+                dx * dx + dy * dy <= radius * radius
+            }
+        }
+    }
+}
+
+/// Exact slope `num / den` with a positive denominator.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Slope {
+    pub(crate) num: i64,
+    pub(crate) den: i64,
+}
+
+impl Slope {
+    /// Slope of the edge of the cell at `col` nearest to the start of the row, measured at the
+    /// center of the row.
+    pub(crate) fn of(depth: i64, col: i64) -> Slope {
+        Slope {
+            num: 2 * col - 1,
+            den: 2 * depth,
+        }
+    }
+}
+
+/// A row of cells at a given depth within a quadrant, lit between two slopes.
+#[derive(Clone, Copy, Debug)]
+struct Row {
+    depth: i64,
+    start: Slope,
+    end: Slope,
+}
+
+impl Row {
+    /// The column of the first lit cell, rounding ties up.
+    fn min_col(&self) -> i64 {
+        (2 * self.depth * self.start.num + self.start.den).div_euclid(2 * self.start.den)
+    }
+    /// The column of the last lit cell, rounding ties down.
+    fn max_col(&self) -> i64 {
+        -(self.end.den - 2 * self.depth * self.end.num).div_euclid(2 * self.end.den)
+    }
+    /// Tests whether the center of the cell at `col` lies within the lit sector.
+    fn is_symmetric(&self, col: i64) -> bool {
+        col * self.start.den >= self.depth * self.start.num
+            && col * self.end.den <= self.depth * self.end.num
+    }
+    fn next(&self) -> Row {
+        Row {
+            depth: self.depth + 1,
+            ..*self
+        }
+    }
+}
+
+/// Maps `(depth, col)` within one of the four quadrants around `origin` to a grid coordinate.
+pub(crate) fn quadrant_point(quadrant: usize, origin: Point, depth: i64, col: i64) -> Point {
+    let (depth, col) = (depth as i32, col as i32);
+    match quadrant {
+        0 => Point::new(origin.x + col, origin.y + depth),
+        1 => Point::new(origin.x + depth, origin.y + col),
+        2 => Point::new(origin.x + col, origin.y - depth),
+        _ => Point::new(origin.x - depth, origin.y + col),
+    }
+}
+
+/// Marks every cell visible from `origin` within `radius` in `visible`, leaving other cells
+/// untouched so that the fields of view of several origins can be accumulated. Cells that are
+/// set in `opaque` block sight, as does everything outside of the grid.
+pub fn compute_fov(
+    opaque: &BoolGrid,
+    origin: Point,
+    radius: u32,
+    metric: Metric,
+    visible: &mut BoolGrid,
+) {
+    assert!(
+        opaque.width == visible.width && opaque.height == visible.height,
+        "cannot write the field of view of a {}x{} grid to a {}x{} grid",
+        opaque.width,
+        opaque.height,
+        visible.width,
+        visible.height
+    );
+    if !opaque.point_in_bounds(origin) {
+        return;
+    }
+    visible.set_point(origin, true);
+    let is_wall = |p: Point| !opaque.point_in_bounds(p) || opaque.get_point(p);
+    for quadrant in 0..4 {
+        let mut rows = vec![Row {
+            depth: 1,
+            start: Slope { num: -1, den: 1 },
+            end: Slope { num: 1, den: 1 },
+        }];
+        while let Some(mut row) = rows.pop() {
+            if row.depth > radius as i64 {
+                continue;
+            }
+            let mut prev_wall = None;
+            for col in row.min_col()..=row.max_col() {
+                let p = quadrant_point(quadrant, origin, row.depth, col);
+                let wall = is_wall(p);
+                if (wall || row.is_symmetric(col))
+                    && visible.point_in_bounds(p)
+                    && metric.within(&origin, &p, radius)
+                {
+                    visible.set_point(p, true);
+                }
+                if prev_wall == Some(true) && !wall {
+                    row.start = Slope::of(row.depth, col);
+                }
+                if prev_wall == Some(false) && wall {
+                    let mut next = row.next();
+                    next.end = Slope::of(row.depth, col);
+                    rows.push(next);
+                }
+                prev_wall = Some(wall);
+            }
+            if prev_wall == Some(false) {
+                rows.push(row.next());
+            }
+        }
+    }
+}
+
+/// Tests whether `b` is visible from `a`, giving the blocking cell closest to `a` otherwise.
+///
+/// This agrees with [compute_fov] without a radius for transparent targets: `b` is visible
+/// exactly when it is in the field of view of `a`, and by symmetry `a` then sees `b` as well.
+/// An opaque target is treated like a transparent one, so only its center is considered.
+pub fn has_line_of_sight(opaque: &BoolGrid, a: Point, b: Point) -> Result<(), Point> {
+    let is_wall = |p: Point| !opaque.point_in_bounds(p) || opaque.get_point(p);
+    if !opaque.point_in_bounds(a) {
+        return Err(a);
+    }
+    if !opaque.point_in_bounds(b) {
+        return Err(b);
+    }
+    let (dx, dy) = ((b.x - a.x) as i64, (b.y - a.y) as i64);
+    let (quadrant, depth, col) = if dy.abs() >= dx.abs() {
+        (if dy >= 0 { 0 } else { 2 }, dy.abs(), dx)
+    } else {
+        (if dx > 0 { 1 } else { 3 }, dx.abs(), dy)
+    };
+    // Follows the lit sector containing the line through the center of `b` row by row. Only
+    // whether the edges of the sector lie exactly on the line matters, in which case the cell
+    // that put them there is kept to report as the blocker.
+    let mut start_on_line: Option<Point> = None;
+    let mut end_on_line: Option<Point> = None;
+    for d in 1..depth {
+        // The line crosses row `d` at column `d * col / depth`, rounded half up to `k`.
+        let t = 2 * d * col + depth;
+        let k = t.div_euclid(2 * depth);
+        let cell = |c: i64| quadrant_point(quadrant, a, d, c);
+        if t.rem_euclid(2 * depth) != 0 {
+            if is_wall(cell(k)) {
+                return Err(cell(k));
+            }
+            continue;
+        }
+        // The line passes exactly between the cells `k - 1` and `k`. Once walls on both sides
+        // have touched it the view is blocked, and the one found in an earlier row is nearer.
+        let (left, right) = (cell(k - 1), cell(k));
+        match (start_on_line, end_on_line) {
+            (Some(s), _) if is_wall(right) => return Err(s),
+            (_, Some(e)) if is_wall(left) => return Err(e),
+            (None, None) => match (is_wall(left), is_wall(right)) {
+                (true, true) => return Err(left),
+                (true, false) => start_on_line = Some(left),
+                (false, true) => end_on_line = Some(right),
+                (false, false) => {}
+            },
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::GridWrite;
+    use crate::rect::Rect;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn fov(opaque: &BoolGrid, origin: Point, radius: u32, metric: Metric) -> BoolGrid {
+        let mut visible = BoolGrid::new(opaque.width, opaque.height, false);
+        compute_fov(opaque, origin, radius, metric, &mut visible);
+        visible
+    }
+
+    #[test]
+    fn test_open_room() {
+        let opaque = BoolGrid::new(21, 21, false);
+        let origin = Point::new(10, 10);
+        assert_eq!(fov(&opaque, origin, 3, Metric::Chebyshev).count_ones(), 49);
+        assert_eq!(fov(&opaque, origin, 3, Metric::Manhattan).count_ones(), 25);
+        assert_eq!(fov(&opaque, origin, 2, Metric::Euclidean).count_ones(), 13);
+        assert_eq!(
+            fov(&opaque, origin, 30, Metric::Chebyshev).count_ones(),
+            441
+        );
+    }
+
+    #[test]
+    fn test_wall_casts_shadow() {
+        let mut opaque = BoolGrid::new(11, 11, false);
+        opaque.set_rect(Rect::new(4, 6, 2, 0), true);
+        let visible = fov(&opaque, Point::new(5, 2), 10, Metric::Chebyshev);
+        // The wall itself is lit but hides what is straight behind it.
+        assert!(visible.get(5, 6) && visible.get(4, 6) && visible.get(6, 6));
+        assert!(!visible.get(5, 7) && !visible.get(5, 10));
+        assert!(visible.get(0, 10) && visible.get(10, 10));
+        // Accumulating a second origin behind the wall.
+        let mut both = visible.clone();
+        compute_fov(&opaque, Point::new(5, 9), 10, Metric::Chebyshev, &mut both);
+        assert!(both.get(5, 7) && both.get(5, 2));
+    }
+
+    #[test]
+    fn test_symmetry() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let mut opaque = BoolGrid::new(16, 16, false);
+        for _ in 0..50 {
+            opaque.set(rng.gen_range(0..16), rng.gen_range(0..16), true);
+        }
+        let floors: Vec<Point> = opaque
+            .cells()
+            .filter(|(_, wall)| !wall)
+            .map(|(p, _)| p)
+            .collect();
+        let fields: Vec<BoolGrid> = floors
+            .iter()
+            .map(|p| fov(&opaque, *p, 20, Metric::Chebyshev))
+            .collect();
+        for (i, a) in floors.iter().enumerate() {
+            for (j, b) in floors.iter().enumerate() {
+                assert_eq!(
+                    fields[i].get_point(*b),
+                    fields[j].get_point(*a),
+                    "{} and {}",
+                    a,
+                    b
+                );
+            }
+        }
+    }
+    #[test]
+    fn test_line_of_sight_matches_fov() {
+        let mut rng = StdRng::seed_from_u64(5);
+        for _ in 0..4 {
+            let mut opaque = BoolGrid::new(14, 12, false);
+            for _ in 0..40 {
+                opaque.set(rng.gen_range(0..14), rng.gen_range(0..12), true);
+            }
+            let floors: Vec<Point> = opaque
+                .cells()
+                .filter(|(_, wall)| !wall)
+                .map(|(p, _)| p)
+                .collect();
+            for a in floors.iter() {
+                let field = fov(&opaque, *a, 100, Metric::Chebyshev);
+                for b in floors.iter() {
+                    let sight = has_line_of_sight(&opaque, *a, *b);
+                    assert_eq!(sight.is_ok(), field.get_point(*b), "{} to {}", a, b);
+                    assert_eq!(sight.is_ok(), has_line_of_sight(&opaque, *b, *a).is_ok());
+                    if let Err(blocker) = sight {
+                        assert!(!opaque.point_in_bounds(blocker) || opaque.get_point(blocker));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_line_of_sight_blocker() {
+        let mut opaque = BoolGrid::new(10, 10, false);
+        opaque.set(4, 2, true);
+        opaque.set(6, 2, true);
+        let a = Point::new(1, 2);
+        assert_eq!(
+            has_line_of_sight(&opaque, a, Point::new(8, 2)),
+            Err(Point::new(4, 2))
+        );
+        assert_eq!(has_line_of_sight(&opaque, a, Point::new(1, 9)), Ok(()));
+        assert_eq!(has_line_of_sight(&opaque, a, a), Ok(()));
+        assert_eq!(
+            has_line_of_sight(&opaque, a, Point::new(10, 2)),
+            Err(Point::new(10, 2))
+        );
+        // The line to (3, 6) from the origin runs between cells on rows 1, 3 and 5, grazing one
+        // wall on each side before it is blocked.
+        let mut opaque = BoolGrid::new(10, 10, false);
+        opaque.set(0, 1, true);
+        opaque.set(2, 3, true);
+        let a = Point::new(0, 0);
+        let b = Point::new(3, 6);
+        assert_eq!(has_line_of_sight(&opaque, a, b), Err(Point::new(0, 1)));
+        assert_eq!(has_line_of_sight(&opaque, b, a), Err(Point::new(2, 3)));
+        opaque.set(2, 3, false);
+        assert_eq!(has_line_of_sight(&opaque, a, b), Ok(()));
+    }
+}
@@ -1,76 +1,245 @@
-//! [Grid] trait abstracting over grid-like containers along with two implementors [BoolGrid] and [SimpleGrid].
-use crate::point::Point;
+//! [Grid] and [ValueGrid] traits abstracting over grid-like containers, layered on [GridDims],
+//! [GridRead] and [GridWrite], along with the implementors [SimpleGrid], [SimpleValueGrid] and
+//! [BoolGrid].
+use crate::components::{self, ComponentInfo};
+use crate::direction::Direction;
+use crate::error::GridError;
+use crate::point::{Neighborhood, Point};
 use crate::rect::Rect;
+use crate::resize::clip_rect_at;
+use crate::validate::{BoolGridData, GridData};
 use serde::{Deserialize, Serialize};
+use std::ops::{
+    BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Index, IndexMut, Not, Sub,
+    SubAssign,
+};
+
+/// The [GridDims] trait holds what all grids have in common: a [width](Self::width) and
+/// [height](Self::height), and how coordinates map to bounds and indices. It underlies [Grid] and
+/// [ValueGrid], as well as [GridRead] and [GridWrite], through which algorithms can accept
+/// either kind of grid.
+pub trait GridDims {
+    fn width(&self) -> usize;
+    fn height(&self) -> usize;
+
+    /// Tests whether a point is in bounds.
+    fn point_in_bounds(&self, point: Point) -> bool {
+        self.index_in_bounds(point.x, point.y)
+    }
+    /// Tests whether an index is in bounds.
+    fn index_in_bounds(&self, x: i32, y: i32) -> bool {
+        x >= 0 && y >= 0 && x < self.width() as i32 && y < self.height() as i32
+    }
+    fn get_ix_point(&self, point: &Point) -> usize {
+        self.compute_ix(point.x, point.y)
+    }
+    /// Gets the index corresponding to a coordinate, which is row-wise.
+    fn compute_ix(&self, x: i32, y: i32) -> usize {
+        x as usize + y as usize * self.width()
+    }
+    /// Retrieves the rectangle corresponding to the grid dimensions at the origin.
+    fn rect(&self) -> Rect {
+        Rect::new(0, 0, self.width() as i32, self.height() as i32)
+    }
+    /// The error for accessing a coordinate which is out of bounds.
+    fn out_of_bounds(&self, x: i32, y: i32) -> GridError {
+        GridError::OutOfBounds {
+            x,
+            y,
+            width: self.width(),
+            height: self.height(),
+        }
+    }
+}
+
+/// Reading values by copy, implemented by all grids. Unlike [ValueGrid::get] it gives `None` for
+/// coordinates out of bounds.
+pub trait GridRead<T>: GridDims {
+    fn read(&self, x: i32, y: i32) -> Option<T>;
+    fn read_point(&self, point: Point) -> Option<T> {
+        self.read(point.x, point.y)
+    }
+    /// Retrieves a column-wise vector of grid values in the given rectangle, skipping the points
+    /// out of bounds.
+    fn get_rect(&self, rect: Rect) -> Vec<T> {
+        rect.points_in()
+            .into_iter()
+            .filter_map(|p| self.read_point(p))
+            .collect::<Vec<T>>()
+    }
+}
+
+/// Writing values, implemented by all grids. Unlike [ValueGrid::set] it gives a [GridError] for
+/// coordinates out of bounds.
+pub trait GridWrite<T>: GridDims {
+    fn write(&mut self, x: i32, y: i32, value: T) -> Result<(), GridError>;
+    fn write_point(&mut self, point: Point, value: T) -> Result<(), GridError> {
+        self.write(point.x, point.y, value)
+    }
+    /// Sets a given rectangle on the grid to the value, skipping the points out of bounds.
+    fn set_rect(&mut self, rect: Rect, value: T)
+    where
+        T: Clone,
+    {
+        for p in rect.points_in() {
+            let _ = self.write_point(p, value.clone());
+        }
+    }
+}
 
 /// The [ValueGrid] trait abstracts over containers of [Clone] and [Copy] items laid out in a rectangle
-/// with a certain [width](Self::width) and [height](Self::height).
-pub trait ValueGrid<T: Clone + Copy> {
+/// with a certain [width](GridDims::width) and [height](GridDims::height).
+pub trait ValueGrid<T: Clone + Copy>: GridDims {
     // Static method signature; `Self` refers to the implementor type.
     fn new(width: usize, height: usize, default_value: T) -> Self;
     fn get(&self, x: i32, y: i32) -> T;
     fn get_point(&self, point: Point) -> T {
         self.get(point.x, point.y)
     }
+    /// Like [get](Self::get) but returns a [GridError] instead of panicking or aliasing into
+    /// another row when the coordinate is out of bounds.
+    fn try_get(&self, x: i32, y: i32) -> Result<T, GridError> {
+        if self.index_in_bounds(x, y) {
+            Ok(self.get(x, y))
+        } else {
+            Err(self.out_of_bounds(x, y))
+        }
+    }
+    fn try_get_point(&self, point: Point) -> Result<T, GridError> {
+        self.try_get(point.x, point.y)
+    }
     fn get_ix(&self, ix: usize) -> T {
         let w = self.width();
         let i = ix % w;
         let j = ix / w;
         self.get(i as i32, j as i32)
     }
-    fn get_ix_point(&self, point: &Point) -> usize {
-        self.compute_ix(point.x, point.y)
-    }
-    /// Gets the index corresponding to a coordinate, which is row-wise.
-    fn compute_ix(&self, x: i32, y: i32) -> usize {
-        x as usize + (y as usize) * self.width()
-    }
-
     fn set(&mut self, x: i32, y: i32, value: T);
     fn set_point(&mut self, point: Point, value: T) {
         self.set(point.x, point.y, value);
     }
+    /// Like [set](Self::set) but returns a [GridError] instead of panicking or aliasing into
+    /// another row when the coordinate is out of bounds.
+    fn try_set(&mut self, x: i32, y: i32, value: T) -> Result<(), GridError> {
+        if self.index_in_bounds(x, y) {
+            self.set(x, y, value);
+            Ok(())
+        } else {
+            Err(self.out_of_bounds(x, y))
+        }
+    }
+    fn try_set_point(&mut self, point: Point, value: T) -> Result<(), GridError> {
+        self.try_set(point.x, point.y, value)
+    }
     fn set_ix(&mut self, ix: usize, value: T) {
         let w = self.width();
         let i = ix % w;
         let j = ix / w;
         self.set(i as i32, j as i32, value);
     }
-    fn width(&self) -> usize;
-    fn height(&self) -> usize;
-
-    /// Tests whether a point is in bounds.
-    fn point_in_bounds(&self, point: Point) -> bool {
-        self.index_in_bounds(point.x, point.y)
+    /// Copies the cells of `src` contained in `src_rect` (see [Rect::contains]) into this grid,
+    /// placing the lower corner of the rectangle at `dest`. Cells falling outside of either grid
+    /// are skipped.
+    fn blit<G: ValueGrid<T>>(&mut self, src: &G, src_rect: Rect, dest: Point) {
+        self.blit_if(src, src_rect, dest, |_, _| true);
     }
-    /// Tests whether an index is in bounds.
-    fn index_in_bounds(&self, x: i32, y: i32) -> bool {
-        x >= 0 && y >= 0 && x < self.width() as i32 && y < self.height() as i32
+    /// Like [blit](Self::blit), but only copies the cells that are set in `mask`, which is
+    /// indexed by the coordinates in `src`.
+    fn blit_masked<G: ValueGrid<T>>(
+        &mut self,
+        src: &G,
+        src_rect: Rect,
+        dest: Point,
+        mask: &BoolGrid,
+    ) {
+        self.blit_if(src, src_rect, dest, |p, _| {
+            mask.point_in_bounds(p) && mask.get_point(p)
+        });
     }
-    /// Sets a given rectangle on the grid to the value.
-    fn set_rect(&mut self, rect: Rect, value: T) {
-        for p in rect.points_in() {
-            self.set_point(p, value);
+    /// Like [blit](Self::blit), but only copies the cells for which the predicate holds given
+    /// their coordinates in `src` and their value.
+    fn blit_if<G: ValueGrid<T>>(
+        &mut self,
+        src: &G,
+        src_rect: Rect,
+        dest: Point,
+        predicate: impl Fn(Point, T) -> bool,
+    ) {
+        let src_origin = Point::new(src.rect().x1, src.rect().y1);
+        let Some(rect) = clip_rect_at(src_rect, src_origin, src.width(), src.height()) else {
+            return;
+        };
+        let offset = dest - Point::new(src_rect.x1, src_rect.y1);
+        for y in rect.y1..=rect.y2 {
+            for x in rect.x1..=rect.x2 {
+                let p = Point::new(x, y);
+                let target = p + offset;
+                if self.point_in_bounds(target) {
+                    let value = src.get_point(p);
+                    if predicate(p, value) {
+                        self.set_point(target, value);
+                    }
+                }
+            }
         }
     }
-    /// Retrieves the rectangle corresponding to the grid dimensions at the origin.
-    fn rect(&self) -> Rect {
-        Rect::new(0, 0, self.width() as i32, self.height() as i32)
+    /// Sets the cells connected to `start` for which the predicate holds to the value, returning
+    /// the number of cells filled.
+    fn flood_fill(
+        &mut self,
+        start: Point,
+        neighborhood: Neighborhood,
+        predicate: impl Fn(T) -> bool,
+        value: T,
+    ) -> usize {
+        let region = components::region(self.width(), self.height(), start, neighborhood, |p| {
+            predicate(self.get_point(p))
+        });
+        for p in region.iter() {
+            self.set_point(*p, value);
+        }
+        region.len()
     }
-    /// Retrieves a column-wise vector of grid values in the given rectangle.
-    fn get_rect(&self, rect: Rect) -> Vec<T> {
-        rect.points_in()
-            .into_iter()
-            .map(|p| self.get_point(p))
-            .collect::<Vec<T>>()
+    /// Labels the connected components of cells with equal values, giving a grid of labels
+    /// indexing into the list of components.
+    fn label_components(
+        &self,
+        neighborhood: Neighborhood,
+    ) -> (SimpleValueGrid<u32>, Vec<ComponentInfo>)
+    where
+        T: PartialEq,
+    {
+        components::label(self.width(), self.height(), neighborhood, |p| {
+            self.get_point(p)
+        })
+    }
+    /// Iterates over all values in row-major order, starting at the origin.
+    fn iter(&self) -> impl Iterator<Item = T> {
+        self.cells().map(|(_, v)| v)
+    }
+    /// Iterates over all values in row-major order along with their coordinates.
+    fn cells(&self) -> impl Iterator<Item = (Point, T)> {
+        let w = self.width() as i32;
+        (0..self.height() as i32)
+            .flat_map(move |y| (0..w).map(move |x| (Point::new(x, y), self.get(x, y))))
+    }
+    /// Iterates over the rows of the grid, starting at `y = 0`.
+    fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = T>> {
+        let w = self.width() as i32;
+        (0..self.height() as i32).map(move |y| (0..w).map(move |x| self.get(x, y)))
+    }
+    /// Iterates over the columns of the grid, starting at `x = 0`.
+    fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = T>> {
+        let h = self.height() as i32;
+        (0..self.width() as i32).map(move |x| (0..h).map(move |y| self.get(x, y)))
     }
 }
 
 /// The [Grid] trait abstracts over containers of items laid out in a rectangle
-/// with a certain [width](Self::width) and [height](Self::height). Elements are
+/// with a certain [width](GridDims::width) and [height](GridDims::height). Elements are
 /// accessed by reference using [get](Self::get), [get_mut](Self::get_mut) and
 /// related functions.
-pub trait Grid<T> {
+pub trait Grid<T>: GridDims {
     // Static method signature; `Self` refers to the implementor type.
     fn new(width: usize, height: usize, default_value: T) -> Self
     where
@@ -85,13 +254,6 @@ pub trait Grid<T> {
         let j = ix / w;
         self.get(i as i32, j as i32)
     }
-    fn get_ix_point(&self, point: &Point) -> usize {
-        self.compute_ix(point.x, point.y)
-    }
-    /// Gets the index corresponding to a coordinate, which is row-wise.
-    fn compute_ix(&self, x: i32, y: i32) -> usize {
-        x as usize + y as usize * self.width()
-    }
     fn get_mut(&mut self, x: i32, y: i32) -> Option<&mut T>;
     fn get_point_mut(&mut self, point: Point) -> Option<&mut T> {
         self.get_mut(point.x, point.y)
@@ -103,7 +265,9 @@ pub trait Grid<T> {
         self.get_mut(i as i32, j as i32)
     }
     fn set(&mut self, x: i32, y: i32, value: T) {
-        self.get_mut(x, y).map(|x| *x = value);
+        if let Some(r) = self.get_mut(x, y) {
+            *r = value;
+        }
     }
     fn set_point(&mut self, point: Point, value: T) {
         self.set(point.x, point.y, value);
@@ -114,53 +278,162 @@ pub trait Grid<T> {
         let j = ix / w;
         self.set(i as i32, j as i32, value);
     }
-    fn width(&self) -> usize;
-    fn height(&self) -> usize;
-
-    /// Tests whether a point is in bounds.
-    fn point_in_bounds(&self, point: Point) -> bool {
-        self.index_in_bounds(point.x, point.y)
-    }
-    /// Tests whether an index is in bounds.
-    fn index_in_bounds(&self, x: i32, y: i32) -> bool {
-        x >= 0 && y >= 0 && x < self.width() as i32 && y < self.height() as i32
+    /// Copies the cells of `src` contained in `src_rect` (see [Rect::contains]) into this grid,
+    /// placing the lower corner of the rectangle at `dest`. Cells falling outside of either grid
+    /// are skipped.
+    fn blit<G: Grid<T>>(&mut self, src: &G, src_rect: Rect, dest: Point)
+    where
+        T: Clone,
+    {
+        self.blit_if(src, src_rect, dest, |_, _| true);
     }
-    /// Sets a given rectangle on the grid to the value.
-    fn set_rect(&mut self, rect: Rect, value: T)
+    /// Like [blit](Self::blit), but only copies the cells that are set in `mask`, which is
+    /// indexed by the coordinates in `src`.
+    fn blit_masked<G: Grid<T>>(&mut self, src: &G, src_rect: Rect, dest: Point, mask: &BoolGrid)
     where
         T: Clone,
     {
-        for p in rect.points_in() {
-            if let Some(r) = self.get_point_mut(p) {
-                *r = value.clone();
+        self.blit_if(src, src_rect, dest, |p, _| {
+            mask.point_in_bounds(p) && mask.get_point(p)
+        });
+    }
+    /// Like [blit](Self::blit), but only copies the cells for which the predicate holds given
+    /// their coordinates in `src` and their value.
+    fn blit_if<G: Grid<T>>(
+        &mut self,
+        src: &G,
+        src_rect: Rect,
+        dest: Point,
+        predicate: impl Fn(Point, &T) -> bool,
+    ) where
+        T: Clone,
+    {
+        let src_origin = Point::new(src.rect().x1, src.rect().y1);
+        let Some(rect) = clip_rect_at(src_rect, src_origin, src.width(), src.height()) else {
+            return;
+        };
+        let offset = dest - Point::new(src_rect.x1, src_rect.y1);
+        for y in rect.y1..=rect.y2 {
+            for x in rect.x1..=rect.x2 {
+                let p = Point::new(x, y);
+                if let (Some(value), Some(target)) =
+                    (src.get_point(p), self.get_point_mut(p + offset))
+                {
+                    if predicate(p, value) {
+                        *target = value.clone();
+                    }
+                }
             }
         }
     }
-    /// Retrieves the rectangle corresponding to the grid dimensions at the origin.
-    fn rect(&self) -> Rect {
-        Rect::new(0, 0, self.width() as i32, self.height() as i32)
-    }
-    /// Retrieves a column-wise vector of grid values in the given rectangle.
-    fn get_rect(&self, rect: Rect) -> Vec<T>
+    /// Sets the cells connected to `start` for which the predicate holds to the value, returning
+    /// the number of cells filled.
+    fn flood_fill(
+        &mut self,
+        start: Point,
+        neighborhood: Neighborhood,
+        predicate: impl Fn(&T) -> bool,
+        value: T,
+    ) -> usize
     where
         T: Clone,
     {
-        rect.points_in()
-            .into_iter()
-            .map(|p| self.get_point(p))
-            .filter_map(|x| x.cloned())
-            .collect::<Vec<T>>()
+        let region = components::region(self.width(), self.height(), start, neighborhood, |p| {
+            self.get_point(p).is_some_and(&predicate)
+        });
+        for p in region.iter() {
+            self.set_point(*p, value.clone());
+        }
+        region.len()
+    }
+    /// Labels the connected components of cells with equal values, giving a grid of labels
+    /// indexing into the list of components.
+    fn label_components(
+        &self,
+        neighborhood: Neighborhood,
+    ) -> (SimpleValueGrid<u32>, Vec<ComponentInfo>)
+    where
+        T: PartialEq,
+    {
+        components::label(self.width(), self.height(), neighborhood, |p| {
+            self.get_point(p)
+        })
+    }
+    /// Iterates over all elements in row-major order, starting at the origin.
+    fn iter<'a>(&'a self) -> impl Iterator<Item = &'a T>
+    where
+        T: 'a,
+    {
+        self.cells().map(|(_, v)| v)
+    }
+    /// Iterates mutably over all elements in row-major order, starting at the origin.
+    ///
+    /// This method has no default, as mutable references to several cells cannot be built from
+    /// [get_mut](Self::get_mut), so adding it is a breaking change for implementors of [Grid],
+    /// which need to provide it from their storage.
+    fn iter_mut<'a>(&'a mut self) -> impl Iterator<Item = &'a mut T>
+    where
+        T: 'a;
+    /// Iterates over all elements in row-major order along with their coordinates.
+    fn cells<'a>(&'a self) -> impl Iterator<Item = (Point, &'a T)>
+    where
+        T: 'a,
+    {
+        let w = self.width() as i32;
+        (0..self.height() as i32).flat_map(move |y| {
+            (0..w).filter_map(move |x| self.get(x, y).map(|v| (Point::new(x, y), v)))
+        })
+    }
+    /// Iterates over the rows of the grid, starting at `y = 0`.
+    fn rows<'a>(&'a self) -> impl Iterator<Item = impl Iterator<Item = &'a T>>
+    where
+        T: 'a,
+    {
+        let w = self.width() as i32;
+        (0..self.height() as i32).map(move |y| (0..w).filter_map(move |x| self.get(x, y)))
+    }
+    /// Iterates over the columns of the grid, starting at `x = 0`.
+    fn columns<'a>(&'a self) -> impl Iterator<Item = impl Iterator<Item = &'a T>>
+    where
+        T: 'a,
+    {
+        let h = self.height() as i32;
+        (0..self.width() as i32).map(move |x| (0..h).filter_map(move |y| self.get(x, y)))
     }
 }
 
 /// Generic [Grid] implementation.
 #[derive(Serialize, Deserialize, Default, Debug)]
+#[serde(try_from = "GridData<T>")]
 pub struct SimpleGrid<T> {
     pub width: usize,
     pub height: usize,
     pub values: Vec<T>,
 }
 
+impl<T> GridDims for SimpleGrid<T> {
+    fn width(&self) -> usize {
+        self.width
+    }
+    fn height(&self) -> usize {
+        self.height
+    }
+}
+
+impl<T: Clone> GridRead<T> for SimpleGrid<T> {
+    fn read(&self, x: i32, y: i32) -> Option<T> {
+        self.get(x, y).cloned()
+    }
+}
+
+impl<T> GridWrite<T> for SimpleGrid<T> {
+    fn write(&mut self, x: i32, y: i32, value: T) -> Result<(), GridError> {
+        let err = self.out_of_bounds(x, y);
+        *self.get_mut(x, y).ok_or(err)? = value;
+        Ok(())
+    }
+}
+
 impl<T> Grid<T> for SimpleGrid<T> {
     fn new(width: usize, height: usize, default_value: T) -> Self
     where
@@ -188,14 +461,68 @@ impl<T> Grid<T> for SimpleGrid<T> {
             None
         }
     }
-    fn width(&self) -> usize {
-        self.width
+    fn iter<'a>(&'a self) -> impl Iterator<Item = &'a T>
+    where
+        T: 'a,
+    {
+        self.values.iter()
     }
-    fn height(&self) -> usize {
-        self.height
+    fn iter_mut<'a>(&'a mut self) -> impl Iterator<Item = &'a mut T>
+    where
+        T: 'a,
+    {
+        self.values.iter_mut()
+    }
+    fn cells<'a>(&'a self) -> impl Iterator<Item = (Point, &'a T)>
+    where
+        T: 'a,
+    {
+        let w = self.width;
+        self.values
+            .iter()
+            .enumerate()
+            .map(move |(ix, v)| (Point::new((ix % w) as i32, (ix / w) as i32), v))
+    }
+    fn rows<'a>(&'a self) -> impl Iterator<Item = impl Iterator<Item = &'a T>>
+    where
+        T: 'a,
+    {
+        let w = self.width;
+        (0..self.height).map(move |y| self.values[y * w..(y + 1) * w].iter())
+    }
+    fn columns<'a>(&'a self) -> impl Iterator<Item = impl Iterator<Item = &'a T>>
+    where
+        T: 'a,
+    {
+        let w = self.width;
+        (0..w).map(move |x| self.values.get(x..).unwrap_or(&[]).iter().step_by(w))
+    }
+}
+
+impl<T> SimpleGrid<T> {
+    /// Constructs a grid from row-major values, failing if their number does not match the
+    /// dimensions.
+    pub fn from_values(
+        width: usize,
+        height: usize,
+        values: Vec<T>,
+    ) -> Result<SimpleGrid<T>, GridError> {
+        if values.len() != width * height {
+            return Err(GridError::InvalidDimensions {
+                width,
+                height,
+                len: values.len(),
+            });
+        }
+        Ok(SimpleGrid {
+            width,
+            height,
+            values,
+        })
     }
 }
 
+#[allow(clippy::clone_on_copy)]
 impl<T: Clone> Clone for SimpleGrid<T> {
     fn clone(&self) -> Self {
         Self {
@@ -206,29 +533,71 @@ impl<T: Clone> Clone for SimpleGrid<T> {
     }
 }
 
-/// Compact bitwise implementation of a [ValGrid] of [bool]'s.
-#[derive(Clone, Default, Serialize, Deserialize, Debug)]
+/// Compact bitwise implementation of a [ValueGrid] of [bool]'s.
+///
+/// Cells are packed row-major into `u64` words, cell `ix` being bit `ix % 64` of word `ix / 64`.
+/// The bits of the last word past `width * height` are padding and are always kept clear.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(try_from = "BoolGridData")]
 pub struct BoolGrid {
     pub width: usize,
     pub height: usize,
     pub values: Vec<u64>,
 }
 
+/// An empty grid, which like all grids holds a word for its padding.
+impl Default for BoolGrid {
+    fn default() -> Self {
+        BoolGrid::new(0, 0, false)
+    }
+}
+
+impl GridDims for BoolGrid {
+    fn width(&self) -> usize {
+        self.width
+    }
+    fn height(&self) -> usize {
+        self.height
+    }
+}
+
+impl GridRead<bool> for BoolGrid {
+    fn read(&self, x: i32, y: i32) -> Option<bool> {
+        self.try_get(x, y).ok()
+    }
+}
+
+impl GridWrite<bool> for BoolGrid {
+    fn write(&mut self, x: i32, y: i32, value: bool) -> Result<(), GridError> {
+        self.try_set(x, y, value)
+    }
+}
+
 impl ValueGrid<bool> for BoolGrid {
     fn new(width: usize, height: usize, default_value: bool) -> Self {
-        let default_value = if default_value { u64::MAX } else { u64::MIN };
-        let values = vec![default_value; 1 + (width * height) / 64_usize];
-        BoolGrid {
-            width,
-            height,
-            values,
-        }
+        BoolGrid::new(width, height, default_value)
     }
     fn get(&self, x: i32, y: i32) -> bool {
+        debug_assert!(
+            self.index_in_bounds(x, y),
+            "coordinate ({},{}) is out of bounds for a {}x{} BoolGrid",
+            x,
+            y,
+            self.width,
+            self.height
+        );
         let ix = self.compute_ix(x, y);
         (self.values[ix / 64] & (1 << (ix % 64))) != 0
     }
     fn set(&mut self, x: i32, y: i32, value: bool) {
+        debug_assert!(
+            self.index_in_bounds(x, y),
+            "coordinate ({},{}) is out of bounds for a {}x{} BoolGrid",
+            x,
+            y,
+            self.width,
+            self.height
+        );
         let ix = self.compute_ix(x, y);
         if value {
             self.values[ix / 64] |= 1 << (ix % 64);
@@ -236,11 +605,18 @@ impl ValueGrid<bool> for BoolGrid {
             self.values[ix / 64] &= !(1 << (ix % 64));
         }
     }
-    fn width(&self) -> usize {
-        self.width
+    fn iter(&self) -> impl Iterator<Item = bool> {
+        Bits::new(&self.values, 0, self.width * self.height)
     }
-    fn height(&self) -> usize {
-        self.height
+    fn cells(&self) -> impl Iterator<Item = (Point, bool)> {
+        let w = self.width;
+        self.iter()
+            .enumerate()
+            .map(move |(ix, v)| (Point::new((ix % w) as i32, (ix / w) as i32), v))
+    }
+    fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = bool>> {
+        let w = self.width;
+        (0..self.height).map(move |y| Bits::new(&self.values, y * w, (y + 1) * w))
     }
 }
 
@@ -248,22 +624,378 @@ impl BoolGrid {
     pub fn new(width: usize, height: usize, default_value: bool) -> BoolGrid {
         let default_value = if default_value { u64::MAX } else { u64::MIN };
         let values = vec![default_value; 1 + (width * height) / 64_usize];
-        BoolGrid {
+        let mut grid = BoolGrid {
             width,
             height,
             values,
+        };
+        grid.clear_padding();
+        grid
+    }
+    /// Mask of the bits of the last word that correspond to cells rather than padding.
+    fn last_word_mask(&self) -> u64 {
+        (1 << ((self.width * self.height) % 64)) - 1
+    }
+    /// Whether any of the padding bits past `width * height` are set.
+    pub(crate) fn has_padding_set(&self) -> bool {
+        let mask = self.last_word_mask();
+        self.values.last().is_some_and(|last| last & !mask != 0)
+    }
+    /// Clears the padding bits past `width * height`, which word-level operations may have set.
+    fn clear_padding(&mut self) {
+        let mask = self.last_word_mask();
+        if let Some(last) = self.values.last_mut() {
+            *last &= mask;
+        }
+    }
+    fn assert_same_dimensions(&self, other: &BoolGrid) {
+        assert!(
+            self.width == other.width && self.height == other.height,
+            "cannot combine a {}x{} BoolGrid with a {}x{} BoolGrid",
+            self.width,
+            self.height,
+            other.width,
+            other.height
+        );
+    }
+    fn zip_in_place(&mut self, other: &BoolGrid, f: impl Fn(u64, u64) -> u64) {
+        self.assert_same_dimensions(other);
+        for (a, b) in self.values.iter_mut().zip(other.values.iter()) {
+            *a = f(*a, *b);
+        }
+        self.clear_padding();
+    }
+    /// Sets each cell to whether it is set in both grids.
+    pub fn and_in_place(&mut self, other: &BoolGrid) {
+        self.zip_in_place(other, |a, b| a & b);
+    }
+    /// Sets each cell to whether it is set in either grid.
+    pub fn or_in_place(&mut self, other: &BoolGrid) {
+        self.zip_in_place(other, |a, b| a | b);
+    }
+    /// Sets each cell to whether it is set in exactly one of the grids.
+    pub fn xor_in_place(&mut self, other: &BoolGrid) {
+        self.zip_in_place(other, |a, b| a ^ b);
+    }
+    /// Clears every cell that is set in the other grid.
+    pub fn and_not_in_place(&mut self, other: &BoolGrid) {
+        self.zip_in_place(other, |a, b| a & !b);
+    }
+    /// Inverts every cell.
+    pub fn not_in_place(&mut self) {
+        for word in self.values.iter_mut() {
+            *word = !*word;
+        }
+        self.clear_padding();
+    }
+    /// Intersection of two grids of the same dimensions.
+    pub fn and(&self, other: &BoolGrid) -> BoolGrid {
+        let mut grid = self.clone();
+        grid.and_in_place(other);
+        grid
+    }
+    /// Union of two grids of the same dimensions.
+    pub fn or(&self, other: &BoolGrid) -> BoolGrid {
+        let mut grid = self.clone();
+        grid.or_in_place(other);
+        grid
+    }
+    /// Symmetric difference of two grids of the same dimensions.
+    pub fn xor(&self, other: &BoolGrid) -> BoolGrid {
+        let mut grid = self.clone();
+        grid.xor_in_place(other);
+        grid
+    }
+    /// Difference of two grids of the same dimensions.
+    pub fn and_not(&self, other: &BoolGrid) -> BoolGrid {
+        let mut grid = self.clone();
+        grid.and_not_in_place(other);
+        grid
+    }
+    /// Counts the number of set cells.
+    pub fn count_ones(&self) -> usize {
+        self.values.iter().map(|w| w.count_ones() as usize).sum()
+    }
+    /// Tests whether any cell is set.
+    pub fn any(&self) -> bool {
+        self.values.iter().any(|w| *w != 0)
+    }
+    /// Tests whether all cells are set, which is trivially the case for an empty grid.
+    pub fn all(&self) -> bool {
+        match self.values.split_last() {
+            Some((last, full)) => {
+                full.iter().all(|w| *w == u64::MAX) && *last == self.last_word_mask()
+            }
+            None => self.width * self.height == 0,
+        }
+    }
+    /// Counts the set bits with indices in `start..end`.
+    fn count_range(&self, start: usize, end: usize) -> usize {
+        let mut count = 0;
+        let mut ix = start;
+        while ix < end {
+            let offset = ix % 64;
+            let n = (64 - offset).min(end - ix);
+            let mask = if n == 64 { u64::MAX } else { (1 << n) - 1 };
+            count += ((self.values[ix / 64] >> offset) & mask).count_ones() as usize;
+            ix += n;
+        }
+        count
+    }
+    /// Counts the set cells contained in the rectangle (see [Rect::contains]), ignoring the parts
+    /// of it that fall outside of the grid.
+    pub fn count_in_rect(&self, rect: Rect) -> usize {
+        let x1 = rect.x1.max(0) as usize;
+        let y1 = rect.y1.max(0) as usize;
+        let x2 = rect.x2.min(self.width as i32 - 1);
+        let y2 = rect.y2.min(self.height as i32 - 1);
+        if x2 < x1 as i32 || y2 < y1 as i32 {
+            return 0;
+        }
+        (y1..=y2 as usize)
+            .map(|y| self.count_range(y * self.width + x1, y * self.width + x2 as usize + 1))
+            .sum()
+    }
+    /// Shifts the bits of the grid by `k` cells towards higher indices, or lower ones if `k` is
+    /// negative, filling with zeroes.
+    fn shift_bits(&self, k: isize) -> Vec<u64> {
+        let n = self.values.len();
+        let word_shift = k.unsigned_abs() / 64;
+        let bit_shift = k.unsigned_abs() % 64;
+        let word = |i: isize| {
+            if i >= 0 && (i as usize) < n {
+                self.values[i as usize]
+            } else {
+                0
+            }
+        };
+        (0..n as isize)
+            .map(|i| {
+                if k >= 0 {
+                    let src = i - word_shift as isize;
+                    if bit_shift == 0 {
+                        word(src)
+                    } else {
+                        word(src) << bit_shift | word(src - 1) >> (64 - bit_shift)
+                    }
+                } else {
+                    let src = i + word_shift as isize;
+                    if bit_shift == 0 {
+                        word(src)
+                    } else {
+                        word(src) >> bit_shift | word(src + 1) << (64 - bit_shift)
+                    }
+                }
+            })
+            .collect()
+    }
+    /// Gives a grid with only the cells of column `x` set.
+    fn column_mask(&self, x: usize) -> BoolGrid {
+        let mut mask = BoolGrid::new(self.width, self.height, false);
+        for y in 0..self.height {
+            let ix = x + y * self.width;
+            mask.values[ix / 64] |= 1 << (ix % 64);
+        }
+        mask
+    }
+    /// Gives the grid with every cell moved one step in the given direction. Cells moved past
+    /// the edge are dropped and vacated cells are cleared.
+    pub fn shift(&self, dir: Direction) -> BoolGrid {
+        if self.width == 0 || self.height == 0 {
+            return self.clone();
+        }
+        let delta = Point::from(dir);
+        let k = delta.x as isize + delta.y as isize * self.width as isize;
+        let mut grid = BoolGrid {
+            width: self.width,
+            height: self.height,
+            values: self.shift_bits(k),
+        };
+        grid.clear_padding();
+        // Cells crossing a row boundary end up in the column on the opposite edge.
+        match delta.x {
+            1 => grid.and_not_in_place(&self.column_mask(0)),
+            -1 => grid.and_not_in_place(&self.column_mask(self.width - 1)),
+            _ => {}
+        }
+        grid
+    }
+    /// Computes the number of set neighbours of every cell as four bit planes, plane `i` holding
+    /// bit `i` of the count. The counts are summed 64 cells at a time using bit-parallel adders.
+    pub fn neighbor_count_planes(&self, neighborhood: Neighborhood) -> [BoolGrid; 4] {
+        let empty = BoolGrid::new(self.width, self.height, false);
+        let mut planes = [empty.clone(), empty.clone(), empty.clone(), empty];
+        for dir in neighborhood.directions() {
+            // Moving every cell towards its neighbour in `dir` puts the neighbour's value in
+            // the cell itself.
+            let shifted = self.shift(dir.rotate_cw(4));
+            for (i, mut carry) in shifted.values.into_iter().enumerate() {
+                for plane in planes.iter_mut() {
+                    let sum = plane.values[i] ^ carry;
+                    carry &= plane.values[i];
+                    plane.values[i] = sum;
+                }
+            }
+        }
+        planes
+    }
+    /// Computes the number of set [Moore](Neighborhood::Moore) or
+    /// [von Neumann](Neighborhood::VonNeumann) neighbours of every cell.
+    pub fn neighbor_count(&self, neighborhood: Neighborhood) -> SimpleValueGrid<u8> {
+        let planes = self.neighbor_count_planes(neighborhood);
+        let mut counts = SimpleValueGrid {
+            width: self.width,
+            height: self.height,
+            values: vec![0; self.width * self.height],
+        };
+        for (i, plane) in planes.iter().enumerate() {
+            for (count, bit) in counts.values.iter_mut().zip(plane.iter()) {
+                *count |= (bit as u8) << i;
+            }
+        }
+        counts
+    }
+    /// Gives the grid of cells with exactly `count` set neighbours, computed without unpacking
+    /// the counts. This makes e.g. a Game of Life step a handful of word-level operations.
+    pub fn neighbor_count_equals(&self, neighborhood: Neighborhood, count: u8) -> BoolGrid {
+        if count >= 16 {
+            return BoolGrid::new(self.width, self.height, false);
+        }
+        let planes = self.neighbor_count_planes(neighborhood);
+        let mut grid = BoolGrid::new(self.width, self.height, true);
+        for (i, plane) in planes.iter().enumerate() {
+            if count & (1 << i) != 0 {
+                grid.and_in_place(plane);
+            } else {
+                grid.and_not_in_place(plane);
+            }
+        }
+        grid
+    }
+    /// Iterates over the points of all cells that are set, skipping empty words entirely.
+    pub fn iter_ones(&self) -> Ones<'_> {
+        Ones {
+            words: &self.values,
+            word_ix: 0,
+            current: self.values.first().copied().unwrap_or(0),
+            width: self.width,
+            len: self.width * self.height,
+        }
+    }
+}
+
+/// Iterator over a range of bits in a packed slice of words, loading one word per 64 bits.
+#[derive(Clone, Debug)]
+pub struct Bits<'a> {
+    words: &'a [u64],
+    ix: usize,
+    end: usize,
+    current: u64,
+}
+
+impl<'a> Bits<'a> {
+    fn new(words: &'a [u64], start: usize, end: usize) -> Bits<'a> {
+        let current = if start < end {
+            words[start / 64] >> (start % 64)
+        } else {
+            0
+        };
+        Bits {
+            words,
+            ix: start,
+            end,
+            current,
         }
     }
 }
 
+impl Iterator for Bits<'_> {
+    type Item = bool;
+    fn next(&mut self) -> Option<bool> {
+        if self.ix >= self.end {
+            return None;
+        }
+        let bit = self.current & 1 != 0;
+        self.current >>= 1;
+        self.ix += 1;
+        if self.ix.is_multiple_of(64) && self.ix < self.end {
+            self.current = self.words[self.ix / 64];
+        }
+        Some(bit)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end.saturating_sub(self.ix);
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for Bits<'_> {}
+
+/// Iterator over the points of the set cells of a [BoolGrid], see [BoolGrid::iter_ones].
+#[derive(Clone, Debug)]
+pub struct Ones<'a> {
+    words: &'a [u64],
+    word_ix: usize,
+    current: u64,
+    width: usize,
+    len: usize,
+}
+
+impl Iterator for Ones<'_> {
+    type Item = Point;
+    fn next(&mut self) -> Option<Point> {
+        while self.current == 0 {
+            self.word_ix += 1;
+            if self.word_ix >= self.words.len() {
+                return None;
+            }
+            self.current = self.words[self.word_ix];
+        }
+        let ix = self.word_ix * 64 + self.current.trailing_zeros() as usize;
+        if ix >= self.len {
+            self.word_ix = self.words.len();
+            self.current = 0;
+            return None;
+        }
+        // Clear the lowest set bit.
+        self.current &= self.current - 1;
+        Some(Point::new(
+            (ix % self.width) as i32,
+            (ix / self.width) as i32,
+        ))
+    }
+}
+
 /// Generic [ValueGrid] implementation for [Clone] and [Copy] items.
 #[derive(Clone, Serialize, Deserialize, Default, Debug)]
+#[serde(try_from = "GridData<T>")]
 pub struct SimpleValueGrid<T: Clone + Copy> {
     pub width: usize,
     pub height: usize,
     pub values: Vec<T>,
 }
 
+impl<T: Clone + Copy> GridDims for SimpleValueGrid<T> {
+    fn width(&self) -> usize {
+        self.width
+    }
+    fn height(&self) -> usize {
+        self.height
+    }
+}
+
+impl<T: Clone + Copy> GridRead<T> for SimpleValueGrid<T> {
+    fn read(&self, x: i32, y: i32) -> Option<T> {
+        self.try_get(x, y).ok()
+    }
+}
+
+impl<T: Clone + Copy> GridWrite<T> for SimpleValueGrid<T> {
+    fn write(&mut self, x: i32, y: i32, value: T) -> Result<(), GridError> {
+        self.try_set(x, y, value)
+    }
+}
+
 impl<T: Clone + Copy> ValueGrid<T> for SimpleValueGrid<T> {
     fn new(width: usize, height: usize, default_value: T) -> Self {
         let symbols = vec![default_value; width * height];
@@ -274,19 +1006,302 @@ impl<T: Clone + Copy> ValueGrid<T> for SimpleValueGrid<T> {
         }
     }
     fn get(&self, x: i32, y: i32) -> T {
+        debug_assert!(
+            self.index_in_bounds(x, y),
+            "coordinate ({},{}) is out of bounds for a {}x{} SimpleValueGrid",
+            x,
+            y,
+            self.width,
+            self.height
+        );
         self.values[self.compute_ix(x, y)]
     }
     fn set(&mut self, x: i32, y: i32, value: T) {
+        debug_assert!(
+            self.index_in_bounds(x, y),
+            "coordinate ({},{}) is out of bounds for a {}x{} SimpleValueGrid",
+            x,
+            y,
+            self.width,
+            self.height
+        );
         let ix = self.compute_ix(x, y);
         self.values[ix] = value;
     }
 
-    fn width(&self) -> usize {
-        self.width
+    fn iter(&self) -> impl Iterator<Item = T> {
+        self.values.iter().copied()
+    }
+
+    fn cells(&self) -> impl Iterator<Item = (Point, T)> {
+        let w = self.width;
+        self.values
+            .iter()
+            .enumerate()
+            .map(move |(ix, v)| (Point::new((ix % w) as i32, (ix / w) as i32), *v))
+    }
+
+    fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = T>> {
+        let w = self.width;
+        (0..self.height).map(move |y| self.values[y * w..(y + 1) * w].iter().copied())
+    }
+
+    fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = T>> {
+        let w = self.width;
+        (0..w).map(move |x| {
+            self.values
+                .get(x..)
+                .unwrap_or(&[])
+                .iter()
+                .step_by(w)
+                .copied()
+        })
+    }
+}
+
+impl<T: Clone + Copy> SimpleValueGrid<T> {
+    /// Constructs a grid from row-major values, failing if their number does not match the
+    /// dimensions.
+    pub fn from_values(
+        width: usize,
+        height: usize,
+        values: Vec<T>,
+    ) -> Result<SimpleValueGrid<T>, GridError> {
+        if values.len() != width * height {
+            return Err(GridError::InvalidDimensions {
+                width,
+                height,
+                len: values.len(),
+            });
+        }
+        Ok(SimpleValueGrid {
+            width,
+            height,
+            values,
+        })
+    }
+    /// Iterates mutably over all values in row-major order, starting at the origin.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.values.iter_mut()
     }
+}
+
+/// Adapter using any [Grid] of [Copy] items as a [ValueGrid], so that algorithms written against
+/// [ValueGrid] also accept grids storing values by reference.
+///
+/// Like [SimpleValueGrid], [get](ValueGrid::get) and [set](ValueGrid::set) panic on coordinates
+/// out of bounds; use [try_get](ValueGrid::try_get) and [try_set](ValueGrid::try_set) to handle
+/// them.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ValueAdapter<G>(pub G);
 
+impl<G> ValueAdapter<G> {
+    /// Unwraps the adapted grid.
+    pub fn into_inner(self) -> G {
+        self.0
+    }
+}
+
+impl<G: GridDims> GridDims for ValueAdapter<G> {
+    fn width(&self) -> usize {
+        self.0.width()
+    }
     fn height(&self) -> usize {
-        self.height
+        self.0.height()
+    }
+    fn index_in_bounds(&self, x: i32, y: i32) -> bool {
+        self.0.index_in_bounds(x, y)
+    }
+    fn compute_ix(&self, x: i32, y: i32) -> usize {
+        self.0.compute_ix(x, y)
+    }
+    fn rect(&self) -> Rect {
+        self.0.rect()
+    }
+}
+
+impl<T, G: GridRead<T>> GridRead<T> for ValueAdapter<G> {
+    fn read(&self, x: i32, y: i32) -> Option<T> {
+        self.0.read(x, y)
+    }
+}
+
+impl<T, G: GridWrite<T>> GridWrite<T> for ValueAdapter<G> {
+    fn write(&mut self, x: i32, y: i32, value: T) -> Result<(), GridError> {
+        self.0.write(x, y, value)
+    }
+}
+
+impl<T: Clone + Copy, G: Grid<T>> ValueGrid<T> for ValueAdapter<G> {
+    fn new(width: usize, height: usize, default_value: T) -> Self {
+        ValueAdapter(G::new(width, height, default_value))
+    }
+    fn get(&self, x: i32, y: i32) -> T {
+        match self.0.get(x, y) {
+            Some(value) => *value,
+            None => panic!("{}", self.out_of_bounds(x, y)),
+        }
+    }
+    fn get_ix(&self, ix: usize) -> T {
+        *self
+            .0
+            .get_ix(ix)
+            .unwrap_or_else(|| panic!("index {} is out of bounds", ix))
+    }
+    fn set(&mut self, x: i32, y: i32, value: T) {
+        let err = self.out_of_bounds(x, y);
+        match self.0.get_mut(x, y) {
+            Some(r) => *r = value,
+            None => panic!("{}", err),
+        }
+    }
+    fn set_ix(&mut self, ix: usize, value: T) {
+        match self.0.get_ix_mut(ix) {
+            Some(r) => *r = value,
+            None => panic!("index {} is out of bounds", ix),
+        }
+    }
+}
+
+/// Computes the row-major index of a coordinate, panicking with a descriptive message when it is
+/// out of bounds.
+fn checked_ix(width: usize, height: usize, x: i32, y: i32) -> usize {
+    if x < 0 || y < 0 || x as usize >= width || y as usize >= height {
+        panic!(
+            "coordinate ({},{}) is out of bounds for a {}x{} grid",
+            x, y, width, height
+        );
+    }
+    x as usize + y as usize * width
+}
+
+/// Checks a row-major index, panicking with a descriptive message when it is out of bounds.
+fn checked_flat_ix(width: usize, height: usize, ix: usize) -> usize {
+    if ix >= width * height {
+        panic!(
+            "index {} is out of bounds for a {}x{} grid",
+            ix, width, height
+        );
+    }
+    ix
+}
+
+/// Implements [Index] and [IndexMut] for [Point], `(i32, i32)` and [usize] indices on a grid
+/// type with public `width`, `height` and row-major `values` fields.
+macro_rules! impl_index {
+    ($grid:ident, $($bounds:tt)*) => {
+        impl<T: $($bounds)*> Index<Point> for $grid<T> {
+            type Output = T;
+            fn index(&self, point: Point) -> &T {
+                &self.values[checked_ix(self.width, self.height, point.x, point.y)]
+            }
+        }
+        impl<T: $($bounds)*> IndexMut<Point> for $grid<T> {
+            fn index_mut(&mut self, point: Point) -> &mut T {
+                &mut self.values[checked_ix(self.width, self.height, point.x, point.y)]
+            }
+        }
+        impl<T: $($bounds)*> Index<(i32, i32)> for $grid<T> {
+            type Output = T;
+            fn index(&self, (x, y): (i32, i32)) -> &T {
+                &self.values[checked_ix(self.width, self.height, x, y)]
+            }
+        }
+        impl<T: $($bounds)*> IndexMut<(i32, i32)> for $grid<T> {
+            fn index_mut(&mut self, (x, y): (i32, i32)) -> &mut T {
+                &mut self.values[checked_ix(self.width, self.height, x, y)]
+            }
+        }
+        impl<T: $($bounds)*> Index<usize> for $grid<T> {
+            type Output = T;
+            fn index(&self, ix: usize) -> &T {
+                &self.values[checked_flat_ix(self.width, self.height, ix)]
+            }
+        }
+        impl<T: $($bounds)*> IndexMut<usize> for $grid<T> {
+            fn index_mut(&mut self, ix: usize) -> &mut T {
+                &mut self.values[checked_flat_ix(self.width, self.height, ix)]
+            }
+        }
+    };
+}
+
+impl_index!(SimpleGrid, Sized);
+impl_index!(SimpleValueGrid, Clone + Copy);
+
+static TRUE: bool = true;
+static FALSE: bool = false;
+
+impl Index<Point> for BoolGrid {
+    type Output = bool;
+    fn index(&self, point: Point) -> &bool {
+        &self[(point.x, point.y)]
+    }
+}
+
+impl Index<(i32, i32)> for BoolGrid {
+    type Output = bool;
+    fn index(&self, (x, y): (i32, i32)) -> &bool {
+        &self[checked_ix(self.width, self.height, x, y)]
+    }
+}
+
+impl Index<usize> for BoolGrid {
+    type Output = bool;
+    fn index(&self, ix: usize) -> &bool {
+        let ix = checked_flat_ix(self.width, self.height, ix);
+        if self.values[ix / 64] & (1 << (ix % 64)) != 0 {
+            &TRUE
+        } else {
+            &FALSE
+        }
+    }
+}
+
+macro_rules! impl_bool_grid_op {
+    ($op:ident, $method:ident, $assign_op:ident, $assign_method:ident, $in_place:ident) => {
+        impl $op<&BoolGrid> for &BoolGrid {
+            type Output = BoolGrid;
+            fn $method(self, rhs: &BoolGrid) -> BoolGrid {
+                let mut grid = self.clone();
+                grid.$in_place(rhs);
+                grid
+            }
+        }
+        impl $op<&BoolGrid> for BoolGrid {
+            type Output = BoolGrid;
+            fn $method(mut self, rhs: &BoolGrid) -> BoolGrid {
+                self.$in_place(rhs);
+                self
+            }
+        }
+        impl $assign_op<&BoolGrid> for BoolGrid {
+            fn $assign_method(&mut self, rhs: &BoolGrid) {
+                self.$in_place(rhs);
+            }
+        }
+    };
+}
+
+impl_bool_grid_op!(BitAnd, bitand, BitAndAssign, bitand_assign, and_in_place);
+impl_bool_grid_op!(BitOr, bitor, BitOrAssign, bitor_assign, or_in_place);
+impl_bool_grid_op!(BitXor, bitxor, BitXorAssign, bitxor_assign, xor_in_place);
+impl_bool_grid_op!(Sub, sub, SubAssign, sub_assign, and_not_in_place);
+
+impl Not for BoolGrid {
+    type Output = BoolGrid;
+    fn not(mut self) -> BoolGrid {
+        self.not_in_place();
+        self
+    }
+}
+
+impl Not for &BoolGrid {
+    type Output = BoolGrid;
+    fn not(self) -> BoolGrid {
+        let mut grid = self.clone();
+        grid.not_in_place();
+        grid
     }
 }
 
@@ -304,6 +1319,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_simple_grid() {
         let mut grid = SimpleGrid::new(3, 2, true);
         assert_eq!(grid.width(), 3);
@@ -311,4 +1327,310 @@ mod tests {
         grid.set(1, 1, false);
         assert_eq!(*grid.get(1, 1).unwrap(), false);
     }
+
+    #[test]
+    fn test_try_get_set() {
+        let mut grid: SimpleValueGrid<u8> = ValueGrid::new(3, 2, 0);
+        assert_eq!(grid.try_set(2, 1, 7), Ok(()));
+        assert_eq!(grid.try_get_point(Point::new(2, 1)), Ok(7));
+        // (3, 0) would alias into (0, 1) without the bounds check.
+        assert_eq!(
+            grid.try_get(3, 0),
+            Err(GridError::OutOfBounds {
+                x: 3,
+                y: 0,
+                width: 3,
+                height: 2
+            })
+        );
+        assert!(grid.try_set(-1, 0, 1).is_err());
+        let mut bools = BoolGrid::new(3, 2, false);
+        assert!(bools.try_set(0, 2, true).is_err());
+        assert_eq!(bools.try_get(0, 1), Ok(false));
+        assert_eq!(
+            SimpleValueGrid::from_values(2, 2, vec![0; 3]).unwrap_err(),
+            GridError::InvalidDimensions {
+                width: 2,
+                height: 2,
+                len: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_index() {
+        let mut grid = SimpleGrid::new(3, 2, 0);
+        grid[Point::new(2, 1)] = 5;
+        grid[(0, 1)] += 1;
+        grid[1] = 2;
+        assert_eq!(grid.values, vec![0, 2, 0, 1, 0, 5]);
+        assert_eq!(grid[(2, 1)], 5);
+        let mut values: SimpleValueGrid<i32> = ValueGrid::new(2, 2, 0);
+        values[Point::new(1, 0)] = 3;
+        assert_eq!(values[1], 3);
+        let mut bools = BoolGrid::new(70, 2, false);
+        bools.set(66, 1, true);
+        assert!(bools[Point::new(66, 1)]);
+        assert!(!bools[(65, 1)]);
+        assert!(bools[136]);
+    }
+
+    #[test]
+    #[should_panic(expected = "coordinate (3,0) is out of bounds for a 3x2 grid")]
+    fn test_index_out_of_bounds() {
+        let grid = SimpleGrid::new(3, 2, 0);
+        let _ = grid[(3, 0)];
+    }
+
+    #[test]
+    fn test_bool_grid_algebra() {
+        let full = BoolGrid::new(10, 7, true);
+        assert_eq!(full.count_ones(), 70);
+        assert!(full.all());
+        let no_words = BoolGrid {
+            width: 0,
+            height: 0,
+            values: Vec::new(),
+        };
+        assert!(no_words.all() && !no_words.any());
+        let mut a = BoolGrid::new(10, 7, false);
+        assert!(!a.any());
+        a.set_rect(Rect::new(0, 0, 4, 6), true);
+        let mut b = BoolGrid::new(10, 7, false);
+        b.set_rect(Rect::new(3, 2, 6, 4), true);
+        assert_eq!(a.count_ones(), 35);
+        assert_eq!(b.count_ones(), 35);
+        assert_eq!((&a & &b).count_ones(), 10);
+        assert_eq!((&a | &b).count_ones(), 60);
+        assert_eq!((&a ^ &b).count_ones(), 50);
+        assert_eq!((&a - &b).count_ones(), 25);
+        assert_eq!(a.and_not(&b), &a - &b);
+        assert_eq!((!&a).count_ones(), 35);
+        assert_eq!(!(!a.clone()), a);
+        assert_eq!(!&full, BoolGrid::new(10, 7, false));
+        let mut c = a.clone();
+        c |= &b;
+        c ^= &full;
+        assert_eq!(c.count_ones(), 10);
+        assert_eq!(a.count_in_rect(Rect::new(2, 1, 10, 10)), 18);
+        assert_eq!(full.count_in_rect(full.rect()), 70);
+        assert_eq!(full.count_in_rect(Rect::new(-5, -5, 2, 2)), 0);
+    }
+
+    #[test]
+    fn test_bool_grid_shift() {
+        // Wide enough for rows to straddle word boundaries.
+        let mut grid = BoolGrid::new(67, 5, false);
+        let points = [
+            Point::new(0, 0),
+            Point::new(63, 0),
+            Point::new(66, 0),
+            Point::new(63, 2),
+            Point::new(0, 4),
+            Point::new(66, 4),
+        ];
+        for p in points {
+            grid.set_point(p, true);
+        }
+        for dir in Neighborhood::Moore.directions() {
+            let shifted = grid.shift(*dir);
+            let expected: Vec<Point> = points
+                .iter()
+                .map(|p| *p + *dir)
+                .filter(|p| grid.point_in_bounds(*p))
+                .collect();
+            let mut actual: Vec<Point> = shifted.iter_ones().collect();
+            actual.sort_by_key(|p| (p.y, p.x));
+            assert_eq!(actual, expected, "shifting {:?}", dir);
+        }
+    }
+
+    #[test]
+    fn test_neighbor_count() {
+        let mut grid = BoolGrid::new(70, 4, false);
+        grid.set_rect(Rect::new(62, 0, 3, 2), true);
+        let counts = grid.neighbor_count(Neighborhood::Moore);
+        let neumann = grid.neighbor_count(Neighborhood::VonNeumann);
+        for (p, count) in counts.cells() {
+            let expected = p
+                .moore_neighborhood()
+                .iter()
+                .filter(|n| grid.point_in_bounds(**n) && grid.get_point(**n))
+                .count();
+            assert_eq!(count as usize, expected, "at {}", p);
+            let expected = p
+                .neumann_neighborhood()
+                .iter()
+                .filter(|n| grid.point_in_bounds(**n) && grid.get_point(**n))
+                .count();
+            assert_eq!(neumann.get_point(p) as usize, expected, "at {}", p);
+        }
+        assert_eq!(counts.get(63, 1), 8);
+        let eight = grid.neighbor_count_equals(Neighborhood::Moore, 8);
+        assert_eq!(
+            eight.iter_ones().collect::<Vec<_>>(),
+            vec![Point::new(63, 1), Point::new(64, 1)]
+        );
+    }
+
+    #[test]
+    fn test_game_of_life_blinker() {
+        let mut grid = BoolGrid::new(5, 5, false);
+        grid.set_rect(Rect::new(1, 2, 2, 0), true);
+        let three = grid.neighbor_count_equals(Neighborhood::Moore, 3);
+        let two = grid.neighbor_count_equals(Neighborhood::Moore, 2);
+        let next = &three | &(&grid & &two);
+        let mut expected = BoolGrid::new(5, 5, false);
+        expected.set_rect(Rect::new(2, 1, 0, 2), true);
+        assert_eq!(next, expected);
+    }
+
+    #[test]
+    fn test_blit() {
+        let room = SimpleGrid::from_values(3, 2, vec![1, 2, 3, 4, 5, 6]).unwrap();
+        let mut level = SimpleGrid::new(4, 4, 0);
+        level.blit(&room, room.rect(), Point::new(2, 3));
+        // Only the lower-left 2x1 part of the room fits.
+        assert_eq!(level.iter().filter(|v| **v != 0).count(), 2);
+        assert_eq!((level[(2, 3)], level[(3, 3)]), (1, 2));
+        let mut level = SimpleGrid::new(4, 4, 0);
+        level.blit(&room, Rect::new(-1, 1, 2, 0), Point::new(0, 0));
+        // The source rectangle is clipped to the room as well, keeping cells at their offset.
+        assert_eq!(level.values[..4], [0, 4, 5, 0]);
+        let mut mask = BoolGrid::new(3, 2, false);
+        mask.set(1, 1, true);
+        let mut level = SimpleGrid::new(4, 4, 0);
+        level.blit_masked(&room, room.rect(), Point::new(1, 1), &mask);
+        assert_eq!(level.iter().filter(|v| **v != 0).count(), 1);
+        assert_eq!(level[(2, 2)], 5);
+        level.blit_if(&room, room.rect(), Point::new(0, 0), |_, v| *v % 2 == 0);
+        assert_eq!(level.values[..4], [0, 2, 0, 0]);
+
+        let mut bools = BoolGrid::new(70, 2, false);
+        let stamp = BoolGrid::new(8, 1, true);
+        bools.blit(&stamp, stamp.rect(), Point::new(66, 1));
+        assert_eq!(bools.count_ones(), 4);
+    }
+
+    #[test]
+    fn test_simple_grid_iter() {
+        let mut grid = SimpleGrid::new(3, 2, 0);
+        for (i, v) in grid.iter_mut().enumerate() {
+            *v = i;
+        }
+        assert_eq!(
+            grid.iter().copied().collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 4, 5]
+        );
+        assert_eq!(*grid.get(2, 1).unwrap(), 5);
+        let (p, v) = grid.cells().nth(4).unwrap();
+        assert_eq!((p, *v), (Point::new(1, 1), 4));
+        let rows: Vec<Vec<usize>> = grid.rows().map(|r| r.copied().collect()).collect();
+        assert_eq!(rows, vec![vec![0, 1, 2], vec![3, 4, 5]]);
+        let cols: Vec<Vec<usize>> = grid.columns().map(|c| c.copied().collect()).collect();
+        assert_eq!(cols, vec![vec![0, 3], vec![1, 4], vec![2, 5]]);
+
+        let empty: SimpleGrid<u8> = SimpleGrid::new(0, 3, 0);
+        assert_eq!(
+            empty.rows().map(|r| r.count()).collect::<Vec<_>>(),
+            vec![0; 3]
+        );
+        let empty_values = SimpleValueGrid::new(0, 3, 0u8);
+        assert_eq!(empty_values.rows().count(), 3);
+        assert_eq!(empty.columns().count(), 0);
+        let flat: SimpleGrid<u8> = SimpleGrid::new(3, 0, 0);
+        assert_eq!(flat.rows().count(), 0);
+        assert_eq!(
+            flat.columns().map(|c| c.count()).collect::<Vec<_>>(),
+            vec![0; 3]
+        );
+        let flat_values = SimpleValueGrid::new(3, 0, 0u8);
+        assert_eq!(
+            flat_values.columns().map(|c| c.count()).collect::<Vec<_>>(),
+            vec![0; 3]
+        );
+        assert_eq!(empty_values.columns().count(), 0);
+    }
+
+    #[test]
+    fn test_bool_grid_iter() {
+        let mut grid = BoolGrid::new(70, 3, false);
+        let set = [
+            Point::new(0, 0),
+            Point::new(63, 0),
+            Point::new(65, 1),
+            Point::new(69, 2),
+        ];
+        for p in set {
+            grid.set_point(p, true);
+        }
+        assert_eq!(grid.iter().count(), 210);
+        assert_eq!(grid.iter_ones().collect::<Vec<_>>(), set.to_vec());
+        let from_cells: Vec<Point> = grid.cells().filter(|(_, v)| *v).map(|(p, _)| p).collect();
+        assert_eq!(from_cells, set.to_vec());
+        for (y, row) in grid.rows().enumerate() {
+            for (x, v) in row.enumerate() {
+                assert_eq!(v, grid.get(x as i32, y as i32));
+            }
+        }
+        assert_eq!(
+            grid.columns().nth(65).unwrap().collect::<Vec<_>>(),
+            vec![false, true, false]
+        );
+    }
+
+    /// Swaps the values at two points through the read/write traits only.
+    fn swap_cells<T, G: GridRead<T> + GridWrite<T>>(grid: &mut G, a: Point, b: Point) -> bool {
+        match (grid.read_point(a), grid.read_point(b)) {
+            (Some(va), Some(vb)) => {
+                grid.write_point(a, vb).unwrap();
+                grid.write_point(b, va).unwrap();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn test_read_write_generic() {
+        let (a, b) = (Point::new(0, 0), Point::new(2, 1));
+        let mut simple = SimpleGrid::new(3, 2, String::new());
+        simple.set_point(a, "a".to_string());
+        assert!(swap_cells(&mut simple, a, b));
+        assert_eq!(simple.read_point(b), Some("a".to_string()));
+        assert!(!swap_cells(&mut simple, a, Point::new(3, 0)));
+
+        let mut values = SimpleValueGrid::new(3, 2, 0u8);
+        values.set_point(a, 7);
+        assert!(swap_cells(&mut values, a, b));
+        assert_eq!((values.get_point(a), values.get_point(b)), (0, 7));
+
+        let mut bools = BoolGrid::new(3, 2, false);
+        bools.set_point(a, true);
+        assert!(swap_cells(&mut bools, a, b));
+        assert!(!bools.get_point(a) && bools.get_point(b));
+        assert_eq!(
+            bools.write(-1, 0, true),
+            Err(GridError::OutOfBounds {
+                x: -1,
+                y: 0,
+                width: 3,
+                height: 2
+            })
+        );
+        assert_eq!(bools.read(3, 0), None);
+    }
+
+    #[test]
+    fn test_value_adapter() {
+        let mut adapted = ValueAdapter(SimpleGrid::new(4, 3, 1u32));
+        adapted.set(1, 2, 5);
+        assert_eq!(adapted.get(1, 2), 5);
+        assert_eq!(adapted.get_ix(9), 5);
+        assert_eq!(adapted.try_get(4, 0).ok(), None);
+        assert_eq!(adapted.iter().sum::<u32>(), 16);
+        let filled = adapted.flood_fill(Point::new(0, 0), Neighborhood::VonNeumann, |v| v == 1, 2);
+        assert_eq!(filled, 11);
+        assert_eq!(adapted.into_inner().values[9], 5);
+    }
 }
@@ -0,0 +1,465 @@
+//! Memory layouts mapping the cells of a grid to storage slots, and [LayoutGrid] storing its cells
+//! in a chosen [Layout].
+//!
+//! Row-major storage ([Linear]) keeps vertical neighbours a whole row apart, so that algorithms
+//! visiting cells by locality, such as breadth-first searches and convolutions on large grids,
+//! miss the cache often. [ZOrder] and [Tiled] store nearby cells close together instead.
+//! [relayout](LayoutGrid::relayout) converts between layouts; `benches/layout.rs` compares them.
+//!
+//! Only [LayoutGrid] supports layouts. [SimpleGrid](crate::grid::SimpleGrid),
+//! [SimpleValueGrid](crate::grid::SimpleValueGrid) and [BoolGrid](crate::grid::BoolGrid) are
+//! always row-major.
+
+use crate::error::GridError;
+use crate::grid::{Grid, GridDims, GridRead, GridWrite};
+use crate::validate::GridData;
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+/// Mapping between the cells of a grid of a certain width and height and the slots storing them.
+/// Slots which do not store a cell are padding.
+pub trait Layout: Clone + Copy + Debug + Default + PartialEq {
+    /// The number of slots, at least `width * height`.
+    fn slots(width: usize, height: usize) -> usize;
+    /// The slot of the cell at `(x, y)`, which must be in bounds.
+    fn index(width: usize, height: usize, x: usize, y: usize) -> usize;
+    /// The cell stored in a slot, or `None` for padding and slots past the end.
+    fn cell(width: usize, height: usize, ix: usize) -> Option<(usize, usize)>;
+}
+
+/// Row-major order, the layout of [SimpleGrid](crate::grid::SimpleGrid) and
+/// [SimpleValueGrid](crate::grid::SimpleValueGrid).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Linear;
+
+impl Layout for Linear {
+    fn slots(width: usize, height: usize) -> usize {
+        width * height
+    }
+    fn index(width: usize, _height: usize, x: usize, y: usize) -> usize {
+        x + y * width
+    }
+    fn cell(width: usize, height: usize, ix: usize) -> Option<(usize, usize)> {
+        (ix < width * height).then(|| (ix % width, ix / width))
+    }
+}
+
+/// Z-order (Morton order), interleaving the bits of `x` and `y` so that every aligned square of
+/// `2^k` by `2^k` cells is contiguous.
+///
+/// The grid is cut along its longer side into square blocks with the shorter side rounded up to a
+/// power of two, which are Z-ordered one after another. This keeps the padding to a few times the
+/// number of cells however far the grid is from square.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ZOrder;
+
+impl ZOrder {
+    /// Side of the square blocks of a grid of the given dimensions, which must not be empty.
+    fn block(width: usize, height: usize) -> usize {
+        width.min(height).next_power_of_two()
+    }
+}
+
+/// Spreads the lower 32 bits of `v` over the even bits of the result.
+fn spread_bits(v: usize) -> usize {
+    let mut v = v as u64 & 0xFFFF_FFFF;
+    v = (v | (v << 16)) & 0x0000_FFFF_0000_FFFF;
+    v = (v | (v << 8)) & 0x00FF_00FF_00FF_00FF;
+    v = (v | (v << 4)) & 0x0F0F_0F0F_0F0F_0F0F;
+    v = (v | (v << 2)) & 0x3333_3333_3333_3333;
+    v = (v | (v << 1)) & 0x5555_5555_5555_5555;
+    v as usize
+}
+
+/// Gathers the even bits of `v`, the inverse of [spread_bits].
+fn gather_bits(v: usize) -> usize {
+    let mut v = v as u64 & 0x5555_5555_5555_5555;
+    v = (v | (v >> 1)) & 0x3333_3333_3333_3333;
+    v = (v | (v >> 2)) & 0x0F0F_0F0F_0F0F_0F0F;
+    v = (v | (v >> 4)) & 0x00FF_00FF_00FF_00FF;
+    v = (v | (v >> 8)) & 0x0000_FFFF_0000_FFFF;
+    v = (v | (v >> 16)) & 0x0000_0000_FFFF_FFFF;
+    v as usize
+}
+
+impl Layout for ZOrder {
+    fn slots(width: usize, height: usize) -> usize {
+        if width == 0 || height == 0 {
+            0
+        } else {
+            // Interleaving is monotonic in both coordinates, so the corner has the highest index.
+            Self::index(width, height, width - 1, height - 1) + 1
+        }
+    }
+    fn index(width: usize, height: usize, x: usize, y: usize) -> usize {
+        let block = Self::block(width, height);
+        let (b, x, y) = if width >= height {
+            (x / block, x % block, y)
+        } else {
+            (y / block, x, y % block)
+        };
+        b * block * block + (spread_bits(x) | (spread_bits(y) << 1))
+    }
+    fn cell(width: usize, height: usize, ix: usize) -> Option<(usize, usize)> {
+        if ix >= Self::slots(width, height) {
+            return None;
+        }
+        let block = Self::block(width, height);
+        let (b, rest) = (ix / (block * block), ix % (block * block));
+        let (x, y) = (gather_bits(rest), gather_bits(rest >> 1));
+        let (x, y) = if width >= height {
+            (b * block + x, y)
+        } else {
+            (x, b * block + y)
+        };
+        (x < width && y < height).then_some((x, y))
+    }
+}
+
+/// Square tiles of `SIZE` by `SIZE` cells stored one after the other, row-major within a tile and
+/// with the tiles themselves in row-major order. Tiles along the right and top edges are cut to
+/// the grid, so that there is no padding.
+///
+/// `SIZE` must be positive, which is checked when the layout is used:
+///
+/// ```compile_fail
+/// use grid_util::grid::Grid;
+/// use grid_util::layout::{LayoutGrid, Tiled};
+///
+/// let grid = LayoutGrid::<u8, Tiled<0>>::new(4, 4, 0);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Tiled<const SIZE: usize>;
+
+impl<const SIZE: usize> Layout for Tiled<SIZE> {
+    fn slots(width: usize, height: usize) -> usize {
+        const { assert!(SIZE > 0, "tiles must not be empty") };
+        width * height
+    }
+    fn index(width: usize, height: usize, x: usize, y: usize) -> usize {
+        const { assert!(SIZE > 0, "tiles must not be empty") };
+        let (tx, lx) = (x / SIZE * SIZE, x % SIZE);
+        let (ty, ly) = (y / SIZE * SIZE, y % SIZE);
+        let tile_height = SIZE.min(height - ty);
+        let tile_width = SIZE.min(width - tx);
+        ty * width + tx * tile_height + ly * tile_width + lx
+    }
+    fn cell(width: usize, height: usize, ix: usize) -> Option<(usize, usize)> {
+        const { assert!(SIZE > 0, "tiles must not be empty") };
+        if ix >= width * height {
+            return None;
+        }
+        let ty = ix / (SIZE * width) * SIZE;
+        let tile_height = SIZE.min(height - ty);
+        let rest = ix - ty * width;
+        let tx = rest / (SIZE * tile_height) * SIZE;
+        let tile_width = SIZE.min(width - tx);
+        let rest = rest - tx * tile_height;
+        Some((tx + rest % tile_width, ty + rest / tile_width))
+    }
+}
+
+/// [Grid] storing its cells in the given [Layout], with [compute_ix](GridDims::compute_ix) giving
+/// their slots in `values`. Padding slots hold copies of the cell at the origin.
+///
+/// Wrap it in a [ValueAdapter](crate::grid::ValueAdapter) to use it as a
+/// [ValueGrid](crate::grid::ValueGrid). It is serialized with its values in row-major order, like
+/// a [SimpleGrid](crate::grid::SimpleGrid), so that the layout can change between saving and
+/// loading.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(
+    try_from = "GridData<T>",
+    bound(deserialize = "T: Deserialize<'de> + Clone")
+)]
+pub struct LayoutGrid<T, L: Layout = Linear> {
+    pub width: usize,
+    pub height: usize,
+    pub values: Vec<T>,
+    #[serde(skip)]
+    layout: PhantomData<L>,
+}
+
+impl<T, L: Layout> LayoutGrid<T, L> {
+    /// Builds a grid by taking the value of each slot from the cell it stores.
+    fn from_cells(width: usize, height: usize, cell: impl Fn(usize, usize) -> T) -> Self {
+        let values = (0..L::slots(width, height))
+            .map(|ix| {
+                let (x, y) = L::cell(width, height, ix).unwrap_or((0, 0));
+                cell(x, y)
+            })
+            .collect();
+        LayoutGrid {
+            width,
+            height,
+            values,
+            layout: PhantomData,
+        }
+    }
+    /// Constructs a grid from row-major values, failing if their number does not match the
+    /// dimensions.
+    pub fn from_values(width: usize, height: usize, values: Vec<T>) -> Result<Self, GridError>
+    where
+        T: Clone,
+    {
+        if values.len() != width * height {
+            return Err(GridError::InvalidDimensions {
+                width,
+                height,
+                len: values.len(),
+            });
+        }
+        Ok(Self::from_cells(width, height, |x, y| {
+            values[x + y * width].clone()
+        }))
+    }
+    /// Constructs a grid from values already in the order of the layout, failing if their number
+    /// does not match its slots.
+    pub fn from_slots(width: usize, height: usize, values: Vec<T>) -> Result<Self, GridError> {
+        if values.len() != L::slots(width, height) {
+            return Err(GridError::InvalidDimensions {
+                width,
+                height,
+                len: values.len(),
+            });
+        }
+        Ok(LayoutGrid {
+            width,
+            height,
+            values,
+            layout: PhantomData,
+        })
+    }
+    /// Copies the grid into another layout.
+    pub fn relayout<M: Layout>(&self) -> LayoutGrid<T, M>
+    where
+        T: Clone,
+    {
+        LayoutGrid::from_cells(self.width, self.height, |x, y| {
+            self.values[L::index(self.width, self.height, x, y)].clone()
+        })
+    }
+    /// Gives the values in row-major order, as taken by [from_values](Self::from_values).
+    pub fn to_values(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        self.relayout::<Linear>().values
+    }
+}
+
+impl<T: Serialize + Clone, L: Layout> Serialize for LayoutGrid<T, L> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        GridData::from(self).serialize(serializer)
+    }
+}
+
+impl<T, L: Layout> GridDims for LayoutGrid<T, L> {
+    fn width(&self) -> usize {
+        self.width
+    }
+    fn height(&self) -> usize {
+        self.height
+    }
+    fn compute_ix(&self, x: i32, y: i32) -> usize {
+        L::index(self.width, self.height, x as usize, y as usize)
+    }
+}
+
+impl<T: Clone, L: Layout> GridRead<T> for LayoutGrid<T, L> {
+    fn read(&self, x: i32, y: i32) -> Option<T> {
+        self.get(x, y).cloned()
+    }
+}
+
+impl<T, L: Layout> GridWrite<T> for LayoutGrid<T, L> {
+    fn write(&mut self, x: i32, y: i32, value: T) -> Result<(), GridError> {
+        let err = self.out_of_bounds(x, y);
+        *self.get_mut(x, y).ok_or(err)? = value;
+        Ok(())
+    }
+}
+
+/// Indices are slots of the layout, as for [compute_ix](GridDims::compute_ix), and
+/// [iter_mut](Grid::iter_mut) visits the cells in the order of the layout.
+impl<T, L: Layout> Grid<T> for LayoutGrid<T, L> {
+    fn new(width: usize, height: usize, default_value: T) -> Self
+    where
+        T: Clone,
+    {
+        LayoutGrid {
+            width,
+            height,
+            values: vec![default_value; L::slots(width, height)],
+            layout: PhantomData,
+        }
+    }
+    fn get(&self, x: i32, y: i32) -> Option<&T> {
+        if self.index_in_bounds(x, y) {
+            Some(&self.values[self.compute_ix(x, y)])
+        } else {
+            None
+        }
+    }
+    fn get_mut(&mut self, x: i32, y: i32) -> Option<&mut T> {
+        if self.index_in_bounds(x, y) {
+            let ix = self.compute_ix(x, y);
+            Some(&mut self.values[ix])
+        } else {
+            None
+        }
+    }
+    fn get_ix(&self, ix: usize) -> Option<&T> {
+        L::cell(self.width, self.height, ix).map(|_| &self.values[ix])
+    }
+    fn get_ix_mut(&mut self, ix: usize) -> Option<&mut T> {
+        L::cell(self.width, self.height, ix).map(|_| &mut self.values[ix])
+    }
+    fn set_ix(&mut self, ix: usize, value: T) {
+        if let Some(v) = self.get_ix_mut(ix) {
+            *v = value;
+        }
+    }
+    fn iter_mut<'a>(&'a mut self) -> impl Iterator<Item = &'a mut T>
+    where
+        T: 'a,
+    {
+        let (width, height) = (self.width, self.height);
+        self.values
+            .iter_mut()
+            .enumerate()
+            .filter(move |(ix, _)| L::cell(width, height, *ix).is_some())
+            .map(|(_, v)| v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{SimpleGrid, ValueAdapter, ValueGrid};
+    use crate::point::{Neighborhood, Point};
+
+    /// Checks that the cells and slots of a layout are in one-to-one correspondence.
+    fn check_layout<L: Layout>(width: usize, height: usize) {
+        let slots = L::slots(width, height);
+        let mut seen = vec![false; slots];
+        for y in 0..height {
+            for x in 0..width {
+                let ix = L::index(width, height, x, y);
+                assert!(
+                    !seen[ix],
+                    "{:?} maps two cells to slot {}",
+                    L::default(),
+                    ix
+                );
+                seen[ix] = true;
+                assert_eq!(L::cell(width, height, ix), Some((x, y)));
+            }
+        }
+        let padding = seen.iter().filter(|s| !**s).count();
+        assert_eq!(padding, slots - width * height);
+        assert_eq!(L::cell(width, height, slots), None);
+    }
+
+    #[test]
+    fn test_layouts() {
+        for (w, h) in [
+            (0, 0),
+            (1, 1),
+            (5, 3),
+            (8, 8),
+            (33, 70),
+            (100, 7),
+            (6, 12),
+            (64, 1),
+        ] {
+            check_layout::<Linear>(w, h);
+            check_layout::<ZOrder>(w, h);
+            check_layout::<Tiled<1>>(w, h);
+            check_layout::<Tiled<4>>(w, h);
+            check_layout::<Tiled<32>>(w, h);
+        }
+        assert_eq!(ZOrder::index(8, 8, 3, 5), 0b100111);
+        assert_eq!(ZOrder::slots(8, 8), 64);
+        assert_eq!(ZOrder::slots(4096, 1), 4096);
+        assert_eq!(ZOrder::index(6, 12, 1, 9), 8 * 8 + 0b0011);
+        for (w, h) in [
+            (4096, 1),
+            (1, 4096),
+            (1000, 3),
+            (5, 3),
+            (33, 70),
+            (1 << 20, 2),
+        ] {
+            assert!(ZOrder::slots(w, h) <= 6 * w * h, "{}x{}", w, h);
+        }
+        // The second 2x2 tile of the top row of tiles is cut to one cell wide.
+        assert_eq!(Tiled::<2>::index(3, 3, 2, 1), 5);
+    }
+
+    #[test]
+    fn test_relayout() {
+        let values: Vec<u32> = (0..37 * 21).collect();
+        let linear = LayoutGrid::<u32>::from_values(37, 21, values.clone()).unwrap();
+        let tiled: LayoutGrid<u32, Tiled<8>> = linear.relayout();
+        let mut z: LayoutGrid<u32, ZOrder> = tiled.relayout();
+        assert_eq!(z.to_values(), values);
+        assert_eq!(z.get(4, 2), Some(&(4 + 2 * 37)));
+        assert_eq!(z.cells().nth(40).unwrap(), (Point::new(3, 1), &40));
+        assert_eq!(z.iter_mut().count(), 37 * 21);
+        assert_eq!(
+            LayoutGrid::<u32, ZOrder>::from_slots(3, 3, vec![0; 9]),
+            Err(GridError::InvalidDimensions {
+                width: 3,
+                height: 3,
+                len: 9
+            })
+        );
+        let simple = SimpleGrid::from_values(37, 21, values).unwrap();
+        let mut grid = LayoutGrid::<u32, ZOrder>::new(37, 21, 0);
+        grid.blit(&simple, simple.rect(), Point::new(0, 0));
+        assert_eq!(grid, z);
+    }
+
+    #[test]
+    fn test_layout_indices() {
+        // The blocks of a 6x12 Z-ordered grid are 8x8, so slots past x = 5 are padding.
+        let mut grid = LayoutGrid::<u32, ZOrder>::new(6, 12, 0);
+        for (x, y) in [(0, 0), (5, 3), (1, 9), (5, 11)] {
+            let ix = grid.compute_ix(x, y);
+            grid.set_ix(ix, (x + 10 * y) as u32 + 1);
+            assert_eq!(grid.get_ix(ix), Some(&((x + 10 * y) as u32 + 1)));
+            assert_eq!(grid.get(x, y), Some(&((x + 10 * y) as u32 + 1)));
+        }
+        let padding = ZOrder::index(8, 8, 6, 0);
+        assert_eq!(ZOrder::cell(6, 12, padding), None);
+        grid.set_ix(padding, 100);
+        assert_eq!(grid.get_ix(padding), None);
+        grid.set_ix(ZOrder::slots(6, 12), 100);
+        assert_eq!(grid.iter().sum::<u32>(), 1 + 36 + 92 + 116);
+    }
+
+    #[test]
+    fn test_layout_value_grid() {
+        let mut grid = ValueAdapter(LayoutGrid::<u8, Tiled<4>>::new(10, 10, 0));
+        grid.set_rect(crate::rect::Rect::new(3, 0, 0, 9), 1);
+        let filled = grid.flood_fill(Point::new(0, 0), Neighborhood::VonNeumann, |v| v == 0, 2);
+        assert_eq!(filled, 30);
+        assert_eq!(grid.get(9, 9), 0);
+        let ix = grid.compute_ix(9, 9);
+        assert_eq!(grid.get_ix(ix), 0);
+
+        let data = ron::to_string(&grid.0).unwrap();
+        let back: LayoutGrid<u8, Tiled<4>> = ron::from_str(&data).unwrap();
+        assert_eq!(back, grid.0);
+        // The values are row-major whatever the layout, so they read back in another one.
+        assert_eq!(data, ron::to_string(&grid.0.relayout::<Linear>()).unwrap());
+        let z: LayoutGrid<u8, ZOrder> = ron::from_str(&data).unwrap();
+        assert_eq!(z.to_values(), grid.0.to_values());
+        let simple: SimpleGrid<u8> = ron::from_str(&data).unwrap();
+        assert_eq!(simple.values, grid.0.to_values());
+        assert!(
+            ron::from_str::<LayoutGrid<u8, ZOrder>>("(width: 3, height: 3, values: [])").is_err()
+        );
+    }
+}
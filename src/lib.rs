@@ -3,16 +3,45 @@
 //! Collection of utility constructs like [Grid](grid::Grid)'s, [Point](point::Point)'s, [Rect](rect::Rect)'s, etc.
 //! All are geared towards a 2D grid with integer coordinates.
 
+pub mod ascii;
+pub mod binary;
+pub mod chunked;
+pub mod compact;
+pub mod components;
+pub mod dijkstra;
 pub mod direction;
+pub mod error;
+pub mod flow;
+pub mod fov;
 pub mod grid;
+pub mod layout;
+pub mod line;
+pub mod movingai;
+pub mod offset;
+pub mod pathfinding;
 pub mod point;
 pub mod rect;
+pub mod resize;
+pub mod transform;
+pub mod validate;
 
+pub use ascii::RowOrder;
+pub use chunked::ChunkedGrid;
+pub use components::ComponentInfo;
 pub use direction::Direction;
+pub use error::GridError;
 pub use grid::BoolGrid;
 pub use grid::Grid;
+pub use grid::GridDims;
+pub use grid::GridRead;
+pub use grid::GridWrite;
 pub use grid::SimpleGrid;
 pub use grid::SimpleValueGrid;
+pub use grid::ValueAdapter;
 pub use grid::ValueGrid;
+pub use layout::LayoutGrid;
+pub use offset::OffsetGrid;
+pub use point::Neighborhood;
 pub use point::Point;
 pub use rect::Rect;
+pub use transform::Transform;
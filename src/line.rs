@@ -0,0 +1,246 @@
+//! Allocation-free line rasterisation between two [Point]'s, see [Point::line_to] and
+//! [Point::supercover_line].
+
+use crate::point::Point;
+
+/// Iterator over the cells of a line using Bresenham's algorithm, from start to end inclusive.
+/// Successive cells are 8-connected, see [Point::line_to].
+#[derive(Clone, Debug)]
+pub struct Line {
+    current: Point,
+    dx: i32,
+    dy: i32,
+    step_x: i32,
+    step_y: i32,
+    error: i32,
+    remaining: usize,
+}
+
+impl Line {
+    pub fn new(start: Point, end: Point) -> Line {
+        let dx = (end.x - start.x).abs();
+        let dy = -(end.y - start.y).abs();
+        Line {
+            current: start,
+            dx,
+            dy,
+            step_x: (end.x - start.x).signum(),
+            step_y: (end.y - start.y).signum(),
+            error: dx + dy,
+            remaining: dx.max(-dy) as usize + 1,
+        }
+    }
+}
+
+impl Iterator for Line {
+    type Item = Point;
+    fn next(&mut self) -> Option<Point> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let point = self.current;
+        self.remaining -= 1;
+        let e2 = 2 * self.error;
+        if e2 >= self.dy {
+            self.error += self.dy;
+            self.current.x += self.step_x;
+        }
+        if e2 <= self.dx {
+            self.error += self.dx;
+            self.current.y += self.step_y;
+        }
+        Some(point)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for Line {}
+
+/// Iterator over every cell touched by the segment between the centers of two cells, from start
+/// to end inclusive. Successive cells are 4-connected, except where the segment passes exactly
+/// through a corner: there both cells beside the corner are included, one after the other, so
+/// that those two are diagonal neighbours. See [Point::supercover_line].
+#[derive(Clone, Debug)]
+pub struct Supercover {
+    current: Point,
+    nx: i64,
+    ny: i64,
+    ix: i64,
+    iy: i64,
+    step_x: i32,
+    step_y: i32,
+    queue: [Point; 3],
+    queued: usize,
+    next_queued: usize,
+    remaining: usize,
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+impl Supercover {
+    pub fn new(start: Point, end: Point) -> Supercover {
+        let nx = (end.x - start.x).abs() as i64;
+        let ny = (end.y - start.y).abs() as i64;
+        // The segment passes through a corner `gcd` times when the reduced slope has two odd
+        // terms, each adding one cell to the `nx + ny + 1` of a 4-connected path.
+        let g = gcd(nx, ny);
+        let corners = if g > 0 && (nx / g) % 2 == 1 && (ny / g) % 2 == 1 {
+            g
+        } else {
+            0
+        };
+        Supercover {
+            current: start,
+            nx,
+            ny,
+            ix: 0,
+            iy: 0,
+            step_x: (end.x - start.x).signum(),
+            step_y: (end.y - start.y).signum(),
+            queue: [start; 3],
+            queued: 1,
+            next_queued: 0,
+            remaining: (nx + ny + 1 + corners) as usize,
+        }
+    }
+}
+
+impl Iterator for Supercover {
+    type Item = Point;
+    fn next(&mut self) -> Option<Point> {
+        if self.next_queued == self.queued {
+            if self.ix >= self.nx && self.iy >= self.ny {
+                return None;
+            }
+            let p = self.current;
+            // Compares where the segment crosses the next vertical and horizontal cell edges.
+            let decision = (1 + 2 * self.ix) * self.ny - (1 + 2 * self.iy) * self.nx;
+            self.next_queued = 0;
+            if decision == 0 {
+                self.current = Point::new(p.x + self.step_x, p.y + self.step_y);
+                self.queue = [
+                    Point::new(p.x + self.step_x, p.y),
+                    Point::new(p.x, p.y + self.step_y),
+                    self.current,
+                ];
+                self.queued = 3;
+                self.ix += 1;
+                self.iy += 1;
+            } else {
+                if decision < 0 {
+                    self.current.x += self.step_x;
+                    self.ix += 1;
+                } else {
+                    self.current.y += self.step_y;
+                    self.iy += 1;
+                }
+                self.queue[0] = self.current;
+                self.queued = 1;
+            }
+        }
+        let p = self.queue[self.next_queued];
+        self.next_queued += 1;
+        self.remaining -= 1;
+        Some(p)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for Supercover {}
+
+impl Point {
+    /// Iterates over the cells of the Bresenham line from this point to the other, both
+    /// inclusive.
+    pub fn line_to(&self, other: &Point) -> Line {
+        Line::new(*self, *other)
+    }
+    /// Iterates over every cell touched by the segment from this point to the other, both
+    /// inclusive.
+    pub fn supercover_line(&self, other: &Point) -> Supercover {
+        Supercover::new(*self, *other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line() {
+        let a = Point::new(0, 0);
+        assert_eq!(a.line_to(&a).collect::<Vec<_>>(), vec![a]);
+        let line: Vec<Point> = a.line_to(&Point::new(5, 2)).collect();
+        assert_eq!(
+            line,
+            vec![
+                Point::new(0, 0),
+                Point::new(1, 0),
+                Point::new(2, 1),
+                Point::new(3, 1),
+                Point::new(4, 2),
+                Point::new(5, 2)
+            ]
+        );
+        for end in Point::new(0, 0).general_moore_neighborhood(7) {
+            let line = a.line_to(&end);
+            assert_eq!(line.len() as i32, a.move_distance(&end) + 1);
+            let points: Vec<Point> = line.collect();
+            assert_eq!(points.last(), Some(&end));
+            for w in points.windows(2) {
+                assert_eq!(w[0].move_distance(&w[1]), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_supercover_line() {
+        let a = Point::new(0, 0);
+        assert_eq!(
+            a.supercover_line(&Point::new(2, 2)).collect::<Vec<_>>(),
+            vec![
+                Point::new(0, 0),
+                Point::new(1, 0),
+                Point::new(0, 1),
+                Point::new(1, 1),
+                Point::new(2, 1),
+                Point::new(1, 2),
+                Point::new(2, 2)
+            ]
+        );
+        // Passes through the corner at (1.5, 0.5) on the way.
+        assert_eq!(
+            Point::new(3, 1)
+                .supercover_line(&Point::new(0, 0))
+                .collect::<Vec<_>>(),
+            vec![
+                Point::new(3, 1),
+                Point::new(2, 1),
+                Point::new(1, 1),
+                Point::new(2, 0),
+                Point::new(1, 0),
+                Point::new(0, 0)
+            ]
+        );
+        for end in a.general_moore_neighborhood(6) {
+            let points: Vec<Point> = a.supercover_line(&end).collect();
+            assert_eq!(points.last(), Some(&end));
+            assert!(points.len() as i32 > a.manhattan_distance(&end));
+            // Every Bresenham cell is also covered.
+            assert!(a.line_to(&end).all(|p| points.contains(&p)));
+            let mut line = a.supercover_line(&end);
+            assert_eq!(line.len(), points.len());
+            line.next();
+            assert_eq!(line.len(), points.len() - 1);
+        }
+    }
+}
@@ -0,0 +1,380 @@
+//! Reading and writing of the `.map` and `.scen` files of the
+//! [MovingAI](https://movingai.com/benchmarks/formats.html) grid pathfinding benchmarks.
+//!
+//! The files list the rows of a map from the top down, with `y = 0` at the top. Maps and
+//! scenarios are converted to the crate's convention of [NORTH](crate::Direction::NORTH) pointing
+//! towards increasing `y`, so the first row of a `.map` file ends up at the highest `y`.
+
+use crate::ascii::{self, RowOrder};
+use crate::error::GridError;
+use crate::grid::{BoolGrid, SimpleGrid, ValueGrid};
+use crate::point::Point;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The terrain types of MovingAI maps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Terrain {
+    /// Passable terrain, written as `.`.
+    Passable,
+    /// Passable terrain, written as `G`.
+    PassableAlt,
+    /// Out of bounds, written as `@`.
+    OutOfBounds,
+    /// Out of bounds, written as `O`.
+    OutOfBoundsAlt,
+    /// Trees, which are impassable, written as `T`.
+    Tree,
+    /// Swamp, which is passable from regular terrain, written as `S`.
+    Swamp,
+    /// Water, which can only be traversed from water, written as `W`.
+    Water,
+}
+
+impl Terrain {
+    pub fn symbol(&self) -> char {
+        match self {
+            Terrain::Passable => '.',
+            Terrain::PassableAlt => 'G',
+            Terrain::OutOfBounds => '@',
+            Terrain::OutOfBoundsAlt => 'O',
+            Terrain::Tree => 'T',
+            Terrain::Swamp => 'S',
+            Terrain::Water => 'W',
+        }
+    }
+    pub fn from_symbol(symbol: char) -> Option<Terrain> {
+        Some(match symbol {
+            '.' => Terrain::Passable,
+            'G' => Terrain::PassableAlt,
+            '@' => Terrain::OutOfBounds,
+            'O' => Terrain::OutOfBoundsAlt,
+            'T' => Terrain::Tree,
+            'S' => Terrain::Swamp,
+            'W' => Terrain::Water,
+            _ => return None,
+        })
+    }
+    /// Whether the terrain can be traversed from regular terrain, which is what the benchmark
+    /// scenarios assume.
+    pub fn is_passable(&self) -> bool {
+        matches!(
+            self,
+            Terrain::Passable | Terrain::PassableAlt | Terrain::Swamp
+        )
+    }
+}
+
+/// Errors that can occur when reading MovingAI files.
+#[derive(Debug)]
+pub enum MovingAiError {
+    Io(io::Error),
+    /// The header of a map is malformed, at the given line counting from 1.
+    InvalidHeader {
+        line: usize,
+    },
+    /// The body of a map is malformed or does not match the dimensions in its header.
+    InvalidMap(GridError),
+    /// A scenario is malformed, at the given line counting from 1.
+    InvalidScenario {
+        line: usize,
+    },
+}
+
+impl fmt::Display for MovingAiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MovingAiError::Io(e) => write!(f, "{}", e),
+            MovingAiError::InvalidHeader { line } => {
+                write!(f, "invalid map header at line {}", line)
+            }
+            MovingAiError::InvalidMap(e) => write!(f, "invalid map: {}", e),
+            MovingAiError::InvalidScenario { line } => {
+                write!(f, "invalid scenario at line {}", line)
+            }
+        }
+    }
+}
+
+impl Error for MovingAiError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MovingAiError::Io(e) => Some(e),
+            MovingAiError::InvalidMap(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for MovingAiError {
+    fn from(e: io::Error) -> Self {
+        MovingAiError::Io(e)
+    }
+}
+
+impl From<GridError> for MovingAiError {
+    fn from(e: GridError) -> Self {
+        MovingAiError::InvalidMap(e)
+    }
+}
+
+/// Parses the contents of a `.map` file.
+pub fn parse_map(text: &str) -> Result<SimpleGrid<Terrain>, MovingAiError> {
+    let mut width = None;
+    let mut height = None;
+    let mut lines = text.lines().enumerate();
+    let map_line = loop {
+        let Some((i, line)) = lines.next() else {
+            return Err(MovingAiError::InvalidHeader {
+                line: text.lines().count() + 1,
+            });
+        };
+        let invalid = MovingAiError::InvalidHeader { line: i + 1 };
+        let mut words = line.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some("type"), Some(_)) => {}
+            (Some("height"), Some(h)) => height = Some(h.parse().map_err(|_| invalid)?),
+            (Some("width"), Some(w)) => width = Some(w.parse().map_err(|_| invalid)?),
+            (Some("map"), None) => break i + 1,
+            _ => return Err(invalid),
+        }
+    };
+    let (Some(width), Some(height)) = (width, height) else {
+        return Err(MovingAiError::InvalidHeader { line: map_line });
+    };
+    let body: Vec<&str> = lines.map(|(_, line)| line).collect();
+    let (w, h, values) = ascii::parse(&body.join("\n"), RowOrder::NorthFirst, |p, c| {
+        Terrain::from_symbol(c).ok_or(GridError::InvalidSymbol {
+            symbol: c,
+            x: p.x,
+            y: p.y,
+        })
+    })?;
+    if (w, h) != (width, height) {
+        return Err(GridError::InvalidDimensions {
+            width,
+            height,
+            len: values.len(),
+        }
+        .into());
+    }
+    Ok(SimpleGrid::from_values(width, height, values)?)
+}
+
+/// Reads a `.map` file.
+pub fn load_map(path: impl AsRef<Path>) -> Result<SimpleGrid<Terrain>, MovingAiError> {
+    parse_map(&fs::read_to_string(path)?)
+}
+
+/// Gives the cells which are [passable](Terrain::is_passable), as used by the pathfinding
+/// functions.
+pub fn passable_grid(terrain: &SimpleGrid<Terrain>) -> BoolGrid {
+    let mut grid = BoolGrid::new(terrain.width, terrain.height, false);
+    for (ix, t) in terrain.values.iter().enumerate() {
+        if t.is_passable() {
+            grid.set_ix(ix, true);
+        }
+    }
+    grid
+}
+
+/// Writes a map in the `.map` format, with `octile` as its type.
+pub fn write_map(terrain: &SimpleGrid<Terrain>) -> String {
+    format!(
+        "type octile\nheight {}\nwidth {}\nmap\n{}\n",
+        terrain.height,
+        terrain.width,
+        terrain.to_ascii(|t| t.symbol())
+    )
+}
+
+/// Writes a grid of passable cells in the `.map` format, using [Terrain::Passable] and
+/// [Terrain::OutOfBounds].
+pub fn write_passable_map(passable: &BoolGrid) -> String {
+    format!(
+        "type octile\nheight {}\nwidth {}\nmap\n{}\n",
+        passable.height,
+        passable.width,
+        passable.to_ascii(|p| if p { '.' } else { '@' })
+    )
+}
+
+/// Writes a map to a `.map` file.
+pub fn save_map(
+    path: impl AsRef<Path>,
+    terrain: &SimpleGrid<Terrain>,
+) -> Result<(), MovingAiError> {
+    Ok(fs::write(path, write_map(terrain))?)
+}
+
+/// A single problem of a `.scen` file, with its points converted to the crate's convention using
+/// the height of the map.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Scenario {
+    pub bucket: u32,
+    /// Path of the map, as given in the file.
+    pub map: String,
+    pub map_width: usize,
+    pub map_height: usize,
+    pub start: Point,
+    pub goal: Point,
+    /// Length of the shortest path with octile moves and no corner cutting.
+    pub optimal_length: f64,
+}
+
+/// Flips a `y` coordinate between the top-down rows of the files and the crate's convention.
+fn flip_y(point: Point, height: usize) -> Point {
+    Point::new(point.x, height as i32 - 1 - point.y)
+}
+
+/// Parses the contents of a `.scen` file.
+pub fn parse_scenarios(text: &str) -> Result<Vec<Scenario>, MovingAiError> {
+    let mut scenarios = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() || (i == 0 && line.starts_with("version")) {
+            continue;
+        }
+        let fields: Vec<&str> = line.split('\t').map(|f| f.trim()).collect();
+        let parsed = (|| {
+            let [bucket, map, map_width, map_height, sx, sy, gx, gy, optimal] = fields[..] else {
+                return None;
+            };
+            let map_height = map_height.parse().ok()?;
+            let point = |x: &str, y: &str| {
+                Some(flip_y(
+                    Point::new(x.parse().ok()?, y.parse().ok()?),
+                    map_height,
+                ))
+            };
+            Some(Scenario {
+                bucket: bucket.parse().ok()?,
+                map: map.to_string(),
+                map_width: map_width.parse().ok()?,
+                map_height,
+                start: point(sx, sy)?,
+                goal: point(gx, gy)?,
+                optimal_length: optimal.parse().ok()?,
+            })
+        })();
+        scenarios.push(parsed.ok_or(MovingAiError::InvalidScenario { line: i + 1 })?);
+    }
+    Ok(scenarios)
+}
+
+/// Reads a `.scen` file.
+pub fn load_scenarios(path: impl AsRef<Path>) -> Result<Vec<Scenario>, MovingAiError> {
+    parse_scenarios(&fs::read_to_string(path)?)
+}
+
+/// Writes scenarios in the `.scen` format.
+pub fn write_scenarios(scenarios: &[Scenario]) -> String {
+    let mut text = String::from("version 1\n");
+    for s in scenarios {
+        let start = flip_y(s.start, s.map_height);
+        let goal = flip_y(s.goal, s.map_height);
+        text.push_str(&format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{:.8}\n",
+            s.bucket,
+            s.map,
+            s.map_width,
+            s.map_height,
+            start.x,
+            start.y,
+            goal.x,
+            goal.y,
+            s.optimal_length
+        ));
+    }
+    text
+}
+
+/// Writes scenarios to a `.scen` file.
+pub fn save_scenarios(path: impl AsRef<Path>, scenarios: &[Scenario]) -> Result<(), MovingAiError> {
+    Ok(fs::write(path, write_scenarios(scenarios))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pathfinding::{astar, Heuristic};
+    use crate::point::Neighborhood;
+
+    const MAP: &str = "type octile
+height 4
+width 5
+map
+.....
+.@@T.
+.@G..
+S..W.
+";
+
+    const SCENARIOS: &str = "version 1
+0\ttest.map\t5\t4\t0\t0\t4\t3\t7.00000000
+0\ttest.map\t5\t4\t0\t3\t0\t1\t2.00000000
+";
+
+    #[test]
+    fn test_map_round_trip() {
+        let terrain = parse_map(MAP).unwrap();
+        assert_eq!((terrain.width, terrain.height), (5, 4));
+        assert_eq!(terrain.values[0], Terrain::Swamp);
+        assert_eq!(terrain.values[15], Terrain::Passable);
+        assert_eq!(write_map(&terrain), MAP);
+        let passable = passable_grid(&terrain);
+        assert_eq!(passable.count_ones(), 15);
+        assert_eq!(
+            parse_map(&write_passable_map(&passable))
+                .map(|t| passable_grid(&t))
+                .unwrap(),
+            passable
+        );
+        assert!(matches!(
+            parse_map(&MAP.replace("width 5", "width 6")),
+            Err(MovingAiError::InvalidMap(
+                GridError::InvalidDimensions { .. }
+            ))
+        ));
+        assert!(matches!(
+            parse_map(&MAP.replace('T', "X")),
+            Err(MovingAiError::InvalidMap(GridError::InvalidSymbol {
+                symbol: 'X',
+                x: 3,
+                y: 2
+            }))
+        ));
+        assert!(matches!(
+            parse_map("type octile\nheight x\n"),
+            Err(MovingAiError::InvalidHeader { line: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_scenarios() {
+        let passable = passable_grid(&parse_map(MAP).unwrap());
+        let scenarios = parse_scenarios(SCENARIOS).unwrap();
+        assert_eq!(scenarios.len(), 2);
+        assert_eq!(scenarios[0].start, Point::new(0, 3));
+        assert_eq!(scenarios[0].goal, Point::new(4, 0));
+        for s in scenarios.iter() {
+            let path = astar(
+                &passable,
+                s.start,
+                s.goal,
+                Neighborhood::Moore,
+                Heuristic::Octile,
+            )
+            .unwrap();
+            assert!((path.cost as f64 - s.optimal_length).abs() < 1e-4);
+        }
+        assert_eq!(write_scenarios(&scenarios), SCENARIOS);
+        assert!(matches!(
+            parse_scenarios("version 1\n0\ttest.map\t5\n"),
+            Err(MovingAiError::InvalidScenario { line: 2 })
+        ));
+    }
+}
@@ -0,0 +1,265 @@
+//! [OffsetGrid] placing a [Grid] or [ValueGrid] at an origin in world coordinates.
+
+use crate::components::ComponentInfo;
+use crate::error::GridError;
+use crate::grid::{Grid, GridDims, GridRead, GridWrite, SimpleValueGrid, ValueGrid};
+use crate::point::{Neighborhood, Point};
+use crate::rect::Rect;
+use serde::{Deserialize, Serialize};
+
+/// Wraps a grid so that its cell `(0, 0)` lies at `origin`, with all coordinates taken and given
+/// in world space, so e.g. [get_point](Grid::get_point) and [rect](GridDims::rect) account for the
+/// origin. Indices are those of the wrapped grid, as computed by
+/// [compute_ix](GridDims::compute_ix).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OffsetGrid<G> {
+    pub origin: Point,
+    pub grid: G,
+}
+
+impl<G> OffsetGrid<G> {
+    /// Places the grid with its cell `(0, 0)` at `origin`.
+    pub fn at(origin: Point, grid: G) -> OffsetGrid<G> {
+        OffsetGrid { origin, grid }
+    }
+    /// Converts a world coordinate to the corresponding coordinate in the wrapped grid.
+    pub fn to_local(&self, point: Point) -> Point {
+        point - self.origin
+    }
+    /// Converts a coordinate in the wrapped grid to the corresponding world coordinate.
+    pub fn to_world(&self, point: Point) -> Point {
+        point + self.origin
+    }
+    pub fn into_inner(self) -> G {
+        self.grid
+    }
+    /// Moves the component bounds and representatives found in the wrapped grid to world space.
+    fn components_to_world(&self, components: &mut [ComponentInfo]) {
+        for c in components.iter_mut() {
+            c.bounds = translate_rect(c.bounds, self.origin);
+            c.representative = c.representative + self.origin;
+        }
+    }
+}
+
+pub(crate) fn translate_rect(rect: Rect, offset: Point) -> Rect {
+    Rect {
+        x1: rect.x1 + offset.x,
+        y1: rect.y1 + offset.y,
+        x2: rect.x2 + offset.x,
+        y2: rect.y2 + offset.y,
+    }
+}
+
+impl<G: GridDims> GridDims for OffsetGrid<G> {
+    fn width(&self) -> usize {
+        self.grid.width()
+    }
+    fn height(&self) -> usize {
+        self.grid.height()
+    }
+    fn index_in_bounds(&self, x: i32, y: i32) -> bool {
+        self.grid
+            .index_in_bounds(x - self.origin.x, y - self.origin.y)
+    }
+    fn compute_ix(&self, x: i32, y: i32) -> usize {
+        self.grid.compute_ix(x - self.origin.x, y - self.origin.y)
+    }
+    /// Retrieves the rectangle of the wrapped grid, moved to the origin.
+    fn rect(&self) -> Rect {
+        translate_rect(self.grid.rect(), self.origin)
+    }
+}
+
+impl<T, G: GridRead<T>> GridRead<T> for OffsetGrid<G> {
+    fn read(&self, x: i32, y: i32) -> Option<T> {
+        self.grid.read(x - self.origin.x, y - self.origin.y)
+    }
+}
+
+impl<T, G: GridWrite<T>> GridWrite<T> for OffsetGrid<G> {
+    fn write(&mut self, x: i32, y: i32, value: T) -> Result<(), GridError> {
+        if !self.index_in_bounds(x, y) {
+            return Err(self.out_of_bounds(x, y));
+        }
+        self.grid.write(x - self.origin.x, y - self.origin.y, value)
+    }
+}
+
+impl<T, G: Grid<T>> Grid<T> for OffsetGrid<G> {
+    /// Creates a grid with its origin at `(0, 0)`.
+    fn new(width: usize, height: usize, default_value: T) -> Self
+    where
+        T: Clone,
+    {
+        OffsetGrid::at(Point::new(0, 0), G::new(width, height, default_value))
+    }
+    fn get(&self, x: i32, y: i32) -> Option<&T> {
+        self.grid.get(x - self.origin.x, y - self.origin.y)
+    }
+    fn get_ix(&self, ix: usize) -> Option<&T> {
+        self.grid.get_ix(ix)
+    }
+    fn get_mut(&mut self, x: i32, y: i32) -> Option<&mut T> {
+        self.grid.get_mut(x - self.origin.x, y - self.origin.y)
+    }
+    fn get_ix_mut(&mut self, ix: usize) -> Option<&mut T> {
+        self.grid.get_ix_mut(ix)
+    }
+    fn set_ix(&mut self, ix: usize, value: T) {
+        self.grid.set_ix(ix, value);
+    }
+    fn flood_fill(
+        &mut self,
+        start: Point,
+        neighborhood: Neighborhood,
+        predicate: impl Fn(&T) -> bool,
+        value: T,
+    ) -> usize
+    where
+        T: Clone,
+    {
+        let start = self.to_local(start);
+        self.grid.flood_fill(start, neighborhood, predicate, value)
+    }
+    /// Labels the components of the wrapped grid. The grid of labels is indexed like the wrapped
+    /// grid, while the components are given in world coordinates.
+    fn label_components(
+        &self,
+        neighborhood: Neighborhood,
+    ) -> (SimpleValueGrid<u32>, Vec<ComponentInfo>)
+    where
+        T: PartialEq,
+    {
+        let (labels, mut components) = self.grid.label_components(neighborhood);
+        self.components_to_world(&mut components);
+        (labels, components)
+    }
+    fn iter<'a>(&'a self) -> impl Iterator<Item = &'a T>
+    where
+        T: 'a,
+    {
+        self.grid.iter()
+    }
+    fn iter_mut<'a>(&'a mut self) -> impl Iterator<Item = &'a mut T>
+    where
+        T: 'a,
+    {
+        self.grid.iter_mut()
+    }
+    fn cells<'a>(&'a self) -> impl Iterator<Item = (Point, &'a T)>
+    where
+        T: 'a,
+    {
+        let origin = self.origin;
+        self.grid.cells().map(move |(p, v)| (p + origin, v))
+    }
+    fn rows<'a>(&'a self) -> impl Iterator<Item = impl Iterator<Item = &'a T>>
+    where
+        T: 'a,
+    {
+        self.grid.rows()
+    }
+    fn columns<'a>(&'a self) -> impl Iterator<Item = impl Iterator<Item = &'a T>>
+    where
+        T: 'a,
+    {
+        self.grid.columns()
+    }
+}
+
+impl<T: Clone + Copy, G: ValueGrid<T>> ValueGrid<T> for OffsetGrid<G> {
+    /// Creates a grid with its origin at `(0, 0)`.
+    fn new(width: usize, height: usize, default_value: T) -> Self {
+        OffsetGrid::at(Point::new(0, 0), G::new(width, height, default_value))
+    }
+    fn get(&self, x: i32, y: i32) -> T {
+        self.grid.get(x - self.origin.x, y - self.origin.y)
+    }
+    fn get_ix(&self, ix: usize) -> T {
+        self.grid.get_ix(ix)
+    }
+    fn set(&mut self, x: i32, y: i32, value: T) {
+        self.grid.set(x - self.origin.x, y - self.origin.y, value);
+    }
+    fn set_ix(&mut self, ix: usize, value: T) {
+        self.grid.set_ix(ix, value);
+    }
+    fn flood_fill(
+        &mut self,
+        start: Point,
+        neighborhood: Neighborhood,
+        predicate: impl Fn(T) -> bool,
+        value: T,
+    ) -> usize {
+        let start = self.to_local(start);
+        self.grid.flood_fill(start, neighborhood, predicate, value)
+    }
+    /// Labels the components of the wrapped grid. The grid of labels is indexed like the wrapped
+    /// grid, while the components are given in world coordinates.
+    fn label_components(
+        &self,
+        neighborhood: Neighborhood,
+    ) -> (SimpleValueGrid<u32>, Vec<ComponentInfo>)
+    where
+        T: PartialEq,
+    {
+        let (labels, mut components) = self.grid.label_components(neighborhood);
+        self.components_to_world(&mut components);
+        (labels, components)
+    }
+    fn iter(&self) -> impl Iterator<Item = T> {
+        self.grid.iter()
+    }
+    fn cells(&self) -> impl Iterator<Item = (Point, T)> {
+        let origin = self.origin;
+        self.grid.cells().map(move |(p, v)| (p + origin, v))
+    }
+    fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = T>> {
+        self.grid.rows()
+    }
+    fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = T>> {
+        self.grid.columns()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{BoolGrid, SimpleGrid};
+
+    #[test]
+    fn test_world_coordinates() {
+        let mut grid = OffsetGrid::at(Point::new(-5, 10), SimpleGrid::new(4, 3, 0));
+        grid.set_point(Point::new(-5, 10), 1);
+        grid.set_point(Point::new(-2, 12), 2);
+        grid.set_point(Point::new(0, 0), 3);
+        assert_eq!(grid.get_point(Point::new(-2, 12)), Some(&2));
+        assert_eq!(grid.grid.get(3, 2), Some(&2));
+        assert_eq!(grid.get(0, 0), None);
+        assert_eq!(grid.rect(), Rect::new(-5, 10, 4, 3));
+        assert_eq!(grid.compute_ix(-2, 12), 11);
+        assert_eq!(grid.get_ix(11), Some(&2));
+        assert_eq!(grid.cells().next(), Some((Point::new(-5, 10), &1)));
+
+        let filled = grid.flood_fill(Point::new(-4, 11), Neighborhood::VonNeumann, |v| *v == 0, 7);
+        assert_eq!(filled, 10);
+        let (_, components) = grid.label_components(Neighborhood::VonNeumann);
+        assert_eq!(components[0].representative, Point::new(-5, 10));
+
+        let mut target = SimpleGrid::new(2, 2, 0);
+        target.blit(&grid, Rect::new(-3, 11, 1, 1), Point::new(0, 0));
+        assert_eq!(target.values, vec![7, 7, 7, 2]);
+    }
+
+    #[test]
+    fn test_value_grid_offset() {
+        let mut grid = OffsetGrid::at(Point::new(100, -100), BoolGrid::new(3, 3, false));
+        grid.set(101, -99, true);
+        assert!(grid.get(101, -99));
+        assert!(grid.grid.get(1, 1));
+        assert!(grid.try_get(0, 0).is_err());
+        let ones: Vec<Point> = grid.cells().filter(|(_, v)| *v).map(|(p, _)| p).collect();
+        assert_eq!(ones, vec![Point::new(101, -99)]);
+    }
+}
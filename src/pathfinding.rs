@@ -0,0 +1,741 @@
+//! Grid pathfinding using [astar] on 4- and 8-connected grids of uniform cost, as well as [jps]
+//! for 8-connected [BoolGrid]'s.
+//!
+//! Straight moves cost 1 and diagonal moves cost [SQRT_2]. Diagonal moves are only allowed when
+//! both adjacent straight moves are passable, so paths never cut corners.
+
+use crate::grid::{BoolGrid, GridDims, GridRead, SimpleValueGrid, ValueGrid};
+use crate::point::{Neighborhood, Point};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::f32::consts::SQRT_2;
+
+/// Passability of the cells of a grid, whose [rect](GridDims::rect), [width](GridDims::width)
+/// and [height](GridDims::height) bound the search.
+pub trait Passable: GridDims {
+    /// Tests whether the point can be entered. Points out of bounds are never visited.
+    fn is_passable(&self, point: Point) -> bool;
+    /// Tests whether a point is in bounds and passable.
+    fn in_bounds_and_passable(&self, point: Point) -> bool {
+        let rect = self.rect();
+        point.x >= rect.x1
+            && point.y >= rect.y1
+            && ((point.x as i64 - rect.x1 as i64) as usize) < self.width()
+            && ((point.y as i64 - rect.y1 as i64) as usize) < self.height()
+            && self.is_passable(point)
+    }
+}
+
+/// Any grid of `bool`'s, such as a [BoolGrid], with `true` marking the passable cells.
+impl<G: GridRead<bool>> Passable for G {
+    fn is_passable(&self, point: Point) -> bool {
+        self.read_point(point) == Some(true)
+    }
+}
+
+/// A grid of costs of entering each cell, where cells with a negative or non-finite cost are
+/// impassable.
+impl Passable for SimpleValueGrid<f32> {
+    fn is_passable(&self, point: Point) -> bool {
+        let cost = self.get_point(point);
+        cost.is_finite() && cost >= 0.0
+    }
+}
+
+/// [Passable] implementation backed by a closure, see [passable_fn].
+#[derive(Clone, Copy, Debug)]
+pub struct PassableFn<F> {
+    width: usize,
+    height: usize,
+    f: F,
+}
+
+impl<F> GridDims for PassableFn<F> {
+    fn width(&self) -> usize {
+        self.width
+    }
+    fn height(&self) -> usize {
+        self.height
+    }
+}
+
+impl<F: Fn(Point) -> bool> Passable for PassableFn<F> {
+    fn is_passable(&self, point: Point) -> bool {
+        (self.f)(point)
+    }
+}
+
+/// View of a [Passable] with the lower corner of its [rect](GridDims::rect) moved to the origin,
+/// so that searches can index its cells row-major from `(0, 0)`.
+pub(crate) struct Local<'a, P> {
+    passable: &'a P,
+    pub(crate) origin: Point,
+}
+
+impl<'a, P: Passable> Local<'a, P> {
+    pub(crate) fn new(passable: &'a P) -> Local<'a, P> {
+        let rect = passable.rect();
+        Local {
+            passable,
+            origin: Point::new(rect.x1, rect.y1),
+        }
+    }
+}
+
+impl<P: Passable> GridDims for Local<'_, P> {
+    fn width(&self) -> usize {
+        self.passable.width()
+    }
+    fn height(&self) -> usize {
+        self.passable.height()
+    }
+}
+
+impl<P: Passable> Passable for Local<'_, P> {
+    fn is_passable(&self, point: Point) -> bool {
+        self.passable.is_passable(point + self.origin)
+    }
+}
+
+/// Uses a closure as the passability of a grid with the given dimensions.
+pub fn passable_fn<F: Fn(Point) -> bool>(width: usize, height: usize, f: F) -> PassableFn<F> {
+    PassableFn { width, height, f }
+}
+
+/// Estimate of the remaining cost used to guide [astar].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Heuristic {
+    /// [Point::manhattan_distance], admissible on 4-connected grids.
+    Manhattan,
+    /// [Point::move_distance], admissible on 8-connected grids but less informed than
+    /// [Octile](Self::Octile).
+    Chebyshev,
+    /// Exact cost on an empty 8-connected grid.
+    Octile,
+    /// [Point::euclidean_distance].
+    Euclidean,
+    /// No estimate, turning the search into Dijkstra's algorithm.
+    Zero,
+}
+
+impl Heuristic {
+    pub fn estimate(&self, from: &Point, to: &Point) -> f32 {
+        match self {
+            Heuristic::Manhattan => from.manhattan_distance(to) as f32,
+            Heuristic::Chebyshev => from.move_distance(to) as f32,
+            Heuristic::Octile => {
+                let dx = (to.x - from.x).abs();
+                let dy = (to.y - from.y).abs();
+                (dx.max(dy) - dx.min(dy)) as f32 + SQRT_2 * dx.min(dy) as f32
+            }
+            Heuristic::Euclidean => from.euclidean_distance(to),
+            Heuristic::Zero => 0.0,
+        }
+    }
+}
+
+/// A path found by a search, along with some statistics.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Path {
+    /// The points of the path, from start to goal inclusive.
+    pub points: Vec<Point>,
+    /// The total cost of the moves along the path.
+    pub cost: f32,
+    /// The number of nodes expanded by the search.
+    pub expanded: usize,
+}
+
+/// Cost of a move by the given delta.
+pub(crate) fn move_cost(delta: Point) -> f32 {
+    if delta.x != 0 && delta.y != 0 {
+        SQRT_2
+    } else {
+        1.0
+    }
+}
+
+/// The passable neighbours of a point along with the costs of moving there, without cutting
+/// corners.
+pub(crate) fn successors<'a, P: Passable>(
+    passable: &'a P,
+    point: Point,
+    neighborhood: Neighborhood,
+) -> impl Iterator<Item = (Point, f32)> + 'a {
+    neighborhood.directions().iter().filter_map(move |dir| {
+        let delta = Point::from(*dir);
+        let next = point + delta;
+        let allowed = passable.in_bounds_and_passable(next)
+            && (!dir.diagonal()
+                || (passable.in_bounds_and_passable(Point::new(next.x, point.y))
+                    && passable.in_bounds_and_passable(Point::new(point.x, next.y))));
+        allowed.then(|| (next, move_cost(delta)))
+    })
+}
+
+/// Entry of the open list. Nodes are ordered by lowest `f`, then lowest `h`, then earliest
+/// insertion, which makes the search fully deterministic.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct OpenNode {
+    pub(crate) f: f32,
+    pub(crate) h: f32,
+    pub(crate) seq: usize,
+    pub(crate) ix: usize,
+}
+
+impl PartialEq for OpenNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OpenNode {}
+
+impl PartialOrd for OpenNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OpenNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed, as BinaryHeap is a max-heap.
+        other
+            .f
+            .total_cmp(&self.f)
+            .then(other.h.total_cmp(&self.h))
+            .then(other.seq.cmp(&self.seq))
+    }
+}
+
+/// Walks the parent links from the goal back to the start.
+pub(crate) fn reconstruct(parents: &[usize], width: usize, goal: usize) -> Vec<Point> {
+    let mut ix = goal;
+    let mut points = vec![Point::new((ix % width) as i32, (ix / width) as i32)];
+    while parents[ix] != ix {
+        ix = parents[ix];
+        points.push(Point::new((ix % width) as i32, (ix / width) as i32));
+    }
+    points.reverse();
+    points
+}
+
+/// Finds a shortest path from `start` to `goal` using A*. The path is optimal when the
+/// heuristic is admissible for the neighbourhood.
+pub fn astar<P: Passable>(
+    passable: &P,
+    start: Point,
+    goal: Point,
+    neighborhood: Neighborhood,
+    heuristic: Heuristic,
+) -> Option<Path> {
+    let local = Local::new(passable);
+    let origin = local.origin;
+    let mut path = astar_local(
+        &local,
+        start - origin,
+        goal - origin,
+        neighborhood,
+        heuristic,
+    )?;
+    for p in path.points.iter_mut() {
+        *p = *p + origin;
+    }
+    Some(path)
+}
+
+/// [astar] on a grid whose cells are indexed from the origin.
+fn astar_local<P: Passable>(
+    passable: &P,
+    start: Point,
+    goal: Point,
+    neighborhood: Neighborhood,
+    heuristic: Heuristic,
+) -> Option<Path> {
+    if !passable.in_bounds_and_passable(start) || !passable.in_bounds_and_passable(goal) {
+        return None;
+    }
+    let width = passable.width();
+    let ix = |p: Point| p.x as usize + p.y as usize * width;
+    let n = width * passable.height();
+    let mut g = vec![f32::INFINITY; n];
+    let mut parents = vec![usize::MAX; n];
+    let mut closed = vec![false; n];
+    let mut open = BinaryHeap::new();
+    let mut seq = 0;
+    let h = heuristic.estimate(&start, &goal);
+    g[ix(start)] = 0.0;
+    parents[ix(start)] = ix(start);
+    open.push(OpenNode {
+        f: h,
+        h,
+        seq,
+        ix: ix(start),
+    });
+    let mut expanded = 0;
+    while let Some(node) = open.pop() {
+        if closed[node.ix] {
+            continue;
+        }
+        closed[node.ix] = true;
+        if node.ix == ix(goal) {
+            return Some(Path {
+                points: reconstruct(&parents, width, node.ix),
+                cost: g[node.ix],
+                expanded,
+            });
+        }
+        expanded += 1;
+        let point = Point::new((node.ix % width) as i32, (node.ix / width) as i32);
+        for (next, cost) in successors(passable, point, neighborhood) {
+            let next_ix = ix(next);
+            let tentative = g[node.ix] + cost;
+            if !closed[next_ix] && tentative < g[next_ix] {
+                g[next_ix] = tentative;
+                parents[next_ix] = node.ix;
+                seq += 1;
+                let h = heuristic.estimate(&next, &goal);
+                open.push(OpenNode {
+                    f: tentative + h,
+                    h,
+                    seq,
+                    ix: next_ix,
+                });
+            }
+        }
+    }
+    None
+}
+
+/// Reads the cells `x..x + 64` of row `y` as bits, cells outside of the grid reading as blocked.
+fn row_bits(grid: &BoolGrid, y: i32, x: i32) -> u64 {
+    let w = grid.width as i32;
+    if y < 0 || y >= grid.height as i32 || x >= w || x <= -64 {
+        return 0;
+    }
+    let start = x.max(0);
+    let bit = y as usize * grid.width + start as usize;
+    let offset = bit % 64;
+    let mut bits = grid.values[bit / 64] >> offset;
+    if offset != 0 && bit / 64 + 1 < grid.values.len() {
+        bits |= grid.values[bit / 64 + 1] << (64 - offset);
+    }
+    let n = w - start;
+    if n < 64 {
+        bits &= (1 << n) - 1;
+    }
+    // Cells left of the grid read as zero.
+    bits << (start - x)
+}
+
+/// Straight horizontal jump from `from` towards `dx`, scanning 64 cells at a time. A cell is a
+/// jump point when it is the goal or when a cell above or below it is open while the one behind
+/// that is blocked.
+fn jump_horizontal(grid: &BoolGrid, from: Point, dx: i32, goal: Point) -> Option<Point> {
+    let y = from.y;
+    let mut x = from.x;
+    loop {
+        // Bit i of each word describes column `base + i`.
+        let base = if dx > 0 { x } else { x - 63 };
+        let open = row_bits(grid, y, base);
+        let above = row_bits(grid, y + 1, base);
+        let below = row_bits(grid, y - 1, base);
+        let above_behind = row_bits(grid, y + 1, base - dx);
+        let below_behind = row_bits(grid, y - 1, base - dx);
+        let blocked = !open;
+        let mut stop = blocked | (above & !above_behind) | (below & !below_behind);
+        if goal.y == y && goal.x >= base && goal.x < base + 64 {
+            stop |= 1 << (goal.x - base);
+        }
+        if stop != 0 {
+            let i = if dx > 0 {
+                stop.trailing_zeros()
+            } else {
+                63 - stop.leading_zeros()
+            };
+            return if blocked & (1 << i) != 0 {
+                None
+            } else {
+                Some(Point::new(base + i as i32, y))
+            };
+        }
+        x += 64 * dx;
+    }
+}
+
+/// Straight vertical jump from `from` towards `dy`.
+fn jump_vertical(grid: &BoolGrid, from: Point, dy: i32, goal: Point) -> Option<Point> {
+    let mut p = from;
+    loop {
+        if !grid.in_bounds_and_passable(p) {
+            return None;
+        }
+        let open = |x: i32, y: i32| grid.in_bounds_and_passable(Point::new(x, y));
+        if p == goal
+            || (open(p.x - 1, p.y) && !open(p.x - 1, p.y - dy))
+            || (open(p.x + 1, p.y) && !open(p.x + 1, p.y - dy))
+        {
+            return Some(p);
+        }
+        p.y += dy;
+    }
+}
+
+/// Jumps from `from` in the direction `delta` until reaching a jump point, if any.
+fn jump(grid: &BoolGrid, from: Point, delta: Point, goal: Point) -> Option<Point> {
+    if delta.y == 0 {
+        return jump_horizontal(grid, from, delta.x, goal);
+    }
+    if delta.x == 0 {
+        return jump_vertical(grid, from, delta.y, goal);
+    }
+    let mut p = from;
+    loop {
+        if !grid.in_bounds_and_passable(p) {
+            return None;
+        }
+        if p == goal
+            || jump_horizontal(grid, Point::new(p.x + delta.x, p.y), delta.x, goal).is_some()
+            || jump_vertical(grid, Point::new(p.x, p.y + delta.y), delta.y, goal).is_some()
+        {
+            return Some(p);
+        }
+        // Continuing diagonally must not cut a corner.
+        if !grid.in_bounds_and_passable(Point::new(p.x + delta.x, p.y))
+            || !grid.in_bounds_and_passable(Point::new(p.x, p.y + delta.y))
+        {
+            return None;
+        }
+        p = p + delta;
+    }
+}
+
+/// The neighbours of a jump point worth jumping towards given the direction it was reached
+/// from, following the pruning rules for movement without corner cutting.
+fn pruned_neighbors(grid: &BoolGrid, p: Point, parent: Option<Point>) -> Vec<Point> {
+    let Some(parent) = parent else {
+        return successors(grid, p, Neighborhood::Moore)
+            .map(|(n, _)| n)
+            .collect();
+    };
+    let open = |x: i32, y: i32| grid.in_bounds_and_passable(Point::new(x, y));
+    let d = parent.dir(&p);
+    let (x, y) = (p.x, p.y);
+    let mut neighbors = Vec::with_capacity(5);
+    if d.x != 0 && d.y != 0 {
+        let open_y = open(x, y + d.y);
+        let open_x = open(x + d.x, y);
+        if open_y {
+            neighbors.push(Point::new(x, y + d.y));
+        }
+        if open_x {
+            neighbors.push(Point::new(x + d.x, y));
+        }
+        if open_x && open_y {
+            neighbors.push(Point::new(x + d.x, y + d.y));
+        }
+    } else if d.x != 0 {
+        let ahead = open(x + d.x, y);
+        let above = open(x, y + 1);
+        let below = open(x, y - 1);
+        if ahead {
+            neighbors.push(Point::new(x + d.x, y));
+            if above {
+                neighbors.push(Point::new(x + d.x, y + 1));
+            }
+            if below {
+                neighbors.push(Point::new(x + d.x, y - 1));
+            }
+        }
+        if above {
+            neighbors.push(Point::new(x, y + 1));
+        }
+        if below {
+            neighbors.push(Point::new(x, y - 1));
+        }
+    } else {
+        let ahead = open(x, y + d.y);
+        let right = open(x + 1, y);
+        let left = open(x - 1, y);
+        if ahead {
+            neighbors.push(Point::new(x, y + d.y));
+            if right {
+                neighbors.push(Point::new(x + 1, y + d.y));
+            }
+            if left {
+                neighbors.push(Point::new(x - 1, y + d.y));
+            }
+        }
+        if right {
+            neighbors.push(Point::new(x + 1, y));
+        }
+        if left {
+            neighbors.push(Point::new(x - 1, y));
+        }
+    }
+    neighbors
+}
+
+/// Finds a shortest path from `start` to `goal` on an 8-connected grid using Jump Point Search,
+/// which only expands the jump points where the path may change direction. Like [astar] it never
+/// cuts corners, and the returned path lists every cell along the way.
+pub fn jps(grid: &BoolGrid, start: Point, goal: Point) -> Option<Path> {
+    if !grid.in_bounds_and_passable(start) || !grid.in_bounds_and_passable(goal) {
+        return None;
+    }
+    let width = grid.width;
+    let ix = |p: Point| p.x as usize + p.y as usize * width;
+    let point = |ix: usize| Point::new((ix % width) as i32, (ix / width) as i32);
+    let n = width * grid.height;
+    let mut g = vec![f32::INFINITY; n];
+    let mut parents = vec![usize::MAX; n];
+    let mut closed = vec![false; n];
+    let mut open = BinaryHeap::new();
+    let mut seq = 0;
+    let h = Heuristic::Octile.estimate(&start, &goal);
+    g[ix(start)] = 0.0;
+    parents[ix(start)] = ix(start);
+    open.push(OpenNode {
+        f: h,
+        h,
+        seq,
+        ix: ix(start),
+    });
+    let mut expanded = 0;
+    while let Some(node) = open.pop() {
+        if closed[node.ix] {
+            continue;
+        }
+        closed[node.ix] = true;
+        if node.ix == ix(goal) {
+            let jump_points = reconstruct(&parents, width, node.ix);
+            let mut points = vec![start];
+            for w in jump_points.windows(2) {
+                let step = w[0].dir(&w[1]);
+                let mut p = w[0];
+                while p != w[1] {
+                    p = p + step;
+                    points.push(p);
+                }
+            }
+            let cost = points.windows(2).map(|w| move_cost(w[1] - w[0])).sum();
+            return Some(Path {
+                points,
+                cost,
+                expanded,
+            });
+        }
+        expanded += 1;
+        let p = point(node.ix);
+        let parent = (parents[node.ix] != node.ix).then(|| point(parents[node.ix]));
+        for neighbor in pruned_neighbors(grid, p, parent) {
+            let Some(jump_point) = jump(grid, neighbor, neighbor - p, goal) else {
+                continue;
+            };
+            let jp_ix = ix(jump_point);
+            let tentative = g[node.ix] + Heuristic::Octile.estimate(&p, &jump_point);
+            if !closed[jp_ix] && tentative < g[jp_ix] {
+                g[jp_ix] = tentative;
+                parents[jp_ix] = node.ix;
+                seq += 1;
+                let h = Heuristic::Octile.estimate(&jump_point, &goal);
+                open.push(OpenNode {
+                    f: tentative + h,
+                    h,
+                    seq,
+                    ix: jp_ix,
+                });
+            }
+        }
+    }
+    None
+}
+
+/// Open 7x5 grid with a wall at x = 3 with a gap at the top, shared by the tests of the searches
+/// here and in [dijkstra](crate::dijkstra) and [flow](crate::flow).
+#[cfg(test)]
+pub(crate) fn walled() -> BoolGrid {
+    use crate::grid::GridWrite;
+    use crate::rect::Rect;
+    let mut grid = BoolGrid::new(7, 5, true);
+    grid.set_rect(Rect::new(3, 0, 0, 3), false);
+    grid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::offset::OffsetGrid;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_search_offset_grid() {
+        let origin = Point::new(100, 100);
+        let grid = OffsetGrid::at(origin, walled());
+        let path = astar(
+            &grid,
+            origin,
+            origin + Point::new(6, 0),
+            Neighborhood::VonNeumann,
+            Heuristic::Manhattan,
+        )
+        .unwrap();
+        let local = astar(
+            &walled(),
+            Point::new(0, 0),
+            Point::new(6, 0),
+            Neighborhood::VonNeumann,
+            Heuristic::Manhattan,
+        )
+        .unwrap();
+        assert_eq!(path.cost, local.cost);
+        assert!(path
+            .points
+            .iter()
+            .zip(&local.points)
+            .all(|(a, b)| *a == *b + origin));
+        assert!(astar(
+            &grid,
+            Point::new(0, 0),
+            Point::new(6, 0),
+            Neighborhood::VonNeumann,
+            Heuristic::Manhattan
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_astar_neumann() {
+        let grid = walled();
+        let path = astar(
+            &grid,
+            Point::new(0, 0),
+            Point::new(6, 0),
+            Neighborhood::VonNeumann,
+            Heuristic::Manhattan,
+        )
+        .unwrap();
+        assert_eq!(path.points.len(), 15);
+        assert_eq!(path.cost, 14.0);
+        assert_eq!(path.points[0], Point::new(0, 0));
+        assert!(path.points.contains(&Point::new(3, 4)));
+        for w in path.points.windows(2) {
+            assert_eq!(w[0].manhattan_distance(&w[1]), 1);
+        }
+        let dijkstra = astar(
+            &grid,
+            Point::new(0, 0),
+            Point::new(6, 0),
+            Neighborhood::VonNeumann,
+            Heuristic::Zero,
+        )
+        .unwrap();
+        assert_eq!(dijkstra.cost, path.cost);
+        assert!(dijkstra.expanded >= path.expanded);
+    }
+
+    #[test]
+    fn test_astar_moore_no_corner_cutting() {
+        let grid = walled();
+        let path = astar(
+            &grid,
+            Point::new(2, 3),
+            Point::new(4, 3),
+            Neighborhood::Moore,
+            Heuristic::Octile,
+        )
+        .unwrap();
+        // Up, across the gap and down; the diagonals past the end of the wall are blocked.
+        assert_eq!(
+            path.points,
+            vec![
+                Point::new(2, 3),
+                Point::new(2, 4),
+                Point::new(3, 4),
+                Point::new(4, 4),
+                Point::new(4, 3)
+            ]
+        );
+        assert_eq!(path.cost, 4.0);
+    }
+
+    #[test]
+    fn test_astar_closure_and_unreachable() {
+        let open = passable_fn(10, 10, |p| p != Point::new(5, 5));
+        let path = astar(
+            &open,
+            Point::new(0, 0),
+            Point::new(9, 9),
+            Neighborhood::Moore,
+            Heuristic::Octile,
+        )
+        .unwrap();
+        // Diagonals next to the blocked cell would cut its corners, so the path keeps a distance
+        // of two from it.
+        assert!((path.cost - (7.0 * SQRT_2 + 4.0)).abs() < 1e-4);
+        let again = astar(
+            &open,
+            Point::new(0, 0),
+            Point::new(9, 9),
+            Neighborhood::Moore,
+            Heuristic::Octile,
+        );
+        assert_eq!(again, Some(path));
+        let mut grid = walled();
+        grid.set(3, 4, false);
+        let none = astar(
+            &grid,
+            Point::new(0, 0),
+            Point::new(6, 0),
+            Neighborhood::Moore,
+            Heuristic::Octile,
+        );
+        assert_eq!(none, None);
+    }
+
+    #[test]
+    fn test_jps_matches_astar() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..40 {
+            // Wide enough for the horizontal scans to cross word boundaries.
+            let mut grid = BoolGrid::new(150, 20, true);
+            for _ in 0..600 {
+                grid.set(rng.gen_range(0..150), rng.gen_range(0..20), false);
+            }
+            let start = Point::new(rng.gen_range(0..150), rng.gen_range(0..20));
+            let goal = Point::new(rng.gen_range(0..150), rng.gen_range(0..20));
+            let expected = astar(&grid, start, goal, Neighborhood::Moore, Heuristic::Octile);
+            let path = jps(&grid, start, goal);
+            assert_eq!(path.is_some(), expected.is_some(), "{} to {}", start, goal);
+            if let (Some(path), Some(expected)) = (path, expected) {
+                assert!(
+                    (path.cost - expected.cost).abs() < 1e-3,
+                    "{} to {}",
+                    start,
+                    goal
+                );
+                assert_eq!(path.points.first(), Some(&start));
+                assert_eq!(path.points.last(), Some(&goal));
+                for w in path.points.windows(2) {
+                    assert!(successors(&grid, w[0], Neighborhood::Moore).any(|(n, _)| n == w[1]));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_jps_open_grid() {
+        let grid = BoolGrid::new(200, 200, true);
+        let path = jps(&grid, Point::new(0, 0), Point::new(199, 150)).unwrap();
+        assert_eq!(path.points.len(), 200);
+        assert!(path.expanded <= 2);
+        assert_eq!(
+            jps(&grid, Point::new(3, 3), Point::new(3, 3))
+                .unwrap()
+                .points
+                .len(),
+            1
+        );
+    }
+}
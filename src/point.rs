@@ -158,6 +158,39 @@ impl Point {
         neigh
     }
 }
+/// Connectivity of a cell to its neighbours on a grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Neighborhood {
+    /// 4-connected, see [Point::neumann_neighborhood].
+    VonNeumann,
+    /// 8-connected, see [Point::moore_neighborhood].
+    Moore,
+}
+
+impl Neighborhood {
+    /// The directions of the neighbours, in the order of their numerical representation.
+    pub fn directions(&self) -> &'static [Direction] {
+        match self {
+            Neighborhood::VonNeumann => &[
+                Direction::NORTH,
+                Direction::EAST,
+                Direction::SOUTH,
+                Direction::WEST,
+            ],
+            Neighborhood::Moore => &[
+                Direction::NORTH,
+                Direction::NORTHEAST,
+                Direction::EAST,
+                Direction::SOUTHEAST,
+                Direction::SOUTH,
+                Direction::SOUTHWEST,
+                Direction::WEST,
+                Direction::NORTHWEST,
+            ],
+        }
+    }
+}
+
 impl fmt::Display for Point {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Point({},{})", self.x, self.y)
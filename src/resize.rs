@@ -0,0 +1,310 @@
+//! Resizing, cropping and padding of [SimpleGrid], [SimpleValueGrid] and [BoolGrid] while
+//! preserving their contents.
+//!
+//! Operations that move the contents return an offset [Point], which is the translation to add
+//! to a coordinate in the old grid to get the coordinate of the same cell in the new grid.
+
+use crate::grid::{BoolGrid, GridDims, SimpleGrid, SimpleValueGrid, ValueGrid};
+use crate::point::Point;
+use crate::rect::Rect;
+
+/// Clips a rectangle to a grid of the given dimensions, giving the inclusive ranges of the
+/// coordinates of the cells it contains (see [Rect::contains]), if any.
+pub(crate) fn clip_rect(rect: Rect, width: usize, height: usize) -> Option<Rect> {
+    clip_rect_at(rect, Point::new(0, 0), width, height)
+}
+
+/// Like [clip_rect] for a grid whose lowest cell is at `origin`.
+pub(crate) fn clip_rect_at(rect: Rect, origin: Point, width: usize, height: usize) -> Option<Rect> {
+    let clipped = Rect {
+        x1: rect.x1.max(origin.x),
+        y1: rect.y1.max(origin.y),
+        x2: rect.x2.min(origin.x + width as i32 - 1),
+        y2: rect.y2.min(origin.y + height as i32 - 1),
+    };
+    if clipped.x1 > clipped.x2 || clipped.y1 > clipped.y2 {
+        None
+    } else {
+        Some(clipped)
+    }
+}
+
+/// Computes the smallest rectangle containing all of the given cells.
+fn bounding_rect(cells: impl Iterator<Item = Point>) -> Option<Rect> {
+    cells.fold(None, |rect: Option<Rect>, p| {
+        Some(match rect {
+            None => Rect {
+                x1: p.x,
+                x2: p.x,
+                y1: p.y,
+                y2: p.y,
+            },
+            Some(r) => Rect {
+                x1: r.x1.min(p.x),
+                x2: r.x2.max(p.x),
+                y1: r.y1.min(p.y),
+                y2: r.y2.max(p.y),
+            },
+        })
+    })
+}
+
+/// Builds the row-major values of a `new_width` by `new_height` grid holding the values of the
+/// old grid translated by the offset, filling the remaining cells.
+fn translate_values<T: Clone>(
+    values: &[T],
+    width: usize,
+    new_width: usize,
+    new_height: usize,
+    offset: Point,
+    fill: T,
+) -> Vec<T> {
+    let mut new_values = vec![fill; new_width * new_height];
+    for (ix, v) in values.iter().enumerate() {
+        let p = Point::new((ix % width) as i32, (ix / width) as i32) + offset;
+        if p.x >= 0 && p.y >= 0 && (p.x as usize) < new_width && (p.y as usize) < new_height {
+            new_values[p.x as usize + p.y as usize * new_width] = v.clone();
+        }
+    }
+    new_values
+}
+
+/// Collects the row-major values of the cells in a clipped rectangle.
+fn crop_values<T: Clone>(values: &[T], width: usize, rect: Rect) -> Vec<T> {
+    (rect.y1..=rect.y2)
+        .flat_map(|y| {
+            let start = rect.x1 as usize + y as usize * width;
+            values[start..=start + (rect.x2 - rect.x1) as usize].iter()
+        })
+        .cloned()
+        .collect()
+}
+
+/// Offset of the contents of a grid cropped to the clipped rectangle.
+fn crop_offset(clipped: Option<Rect>) -> Point {
+    clipped.map_or(Point::new(0, 0), |r| Point::new(-r.x1, -r.y1))
+}
+
+fn cropped_dimensions(rect: Option<Rect>) -> (usize, usize) {
+    rect.map_or((0, 0), |r| {
+        ((r.x2 - r.x1 + 1) as usize, (r.y2 - r.y1 + 1) as usize)
+    })
+}
+
+impl<T: Clone> SimpleGrid<T> {
+    /// Changes the dimensions of the grid, keeping the values at their coordinates and filling
+    /// new cells.
+    pub fn resize(&mut self, width: usize, height: usize, fill: T) {
+        self.values = translate_values(
+            &self.values,
+            self.width,
+            width,
+            height,
+            Point::new(0, 0),
+            fill,
+        );
+        self.width = width;
+        self.height = height;
+    }
+    /// Gives the part of the grid contained in the rectangle (see [Rect::contains]), clipped
+    /// to the grid, along with the offset of the contents, minus the clipped lower corner.
+    pub fn crop(&self, rect: Rect) -> (SimpleGrid<T>, Point) {
+        let clipped = clip_rect(rect, self.width, self.height);
+        let (width, height) = cropped_dimensions(clipped);
+        let grid = SimpleGrid {
+            width,
+            height,
+            values: clipped.map_or(vec![], |r| crop_values(&self.values, self.width, r)),
+        };
+        (grid, crop_offset(clipped))
+    }
+    /// Adds filled rows and columns on each side, `top` being the side of increasing `y`.
+    /// Returns the offset of the contents.
+    pub fn pad(&mut self, left: usize, right: usize, top: usize, bottom: usize, fill: T) -> Point {
+        let offset = Point::new(left as i32, bottom as i32);
+        let width = left + self.width + right;
+        let height = bottom + self.height + top;
+        self.values = translate_values(&self.values, self.width, width, height, offset, fill);
+        self.width = width;
+        self.height = height;
+        offset
+    }
+    /// Crops the grid to the bounding box of the cells matching the predicate, leaving an empty
+    /// grid if there are none. Returns the offset of the contents.
+    pub fn shrink_to_fit(&mut self, predicate: impl Fn(&T) -> bool) -> Point {
+        let w = self.width;
+        let bounds = bounding_rect(
+            self.values
+                .iter()
+                .enumerate()
+                .filter(|(_, v)| predicate(v))
+                .map(|(ix, _)| Point::new((ix % w) as i32, (ix / w) as i32)),
+        );
+        let (grid, offset) = self.crop(bounds.unwrap_or(Rect::new(0, 0, -1, -1)));
+        *self = grid;
+        offset
+    }
+}
+
+impl<T: Clone + Copy> SimpleValueGrid<T> {
+    /// Changes the dimensions of the grid, keeping the values at their coordinates and filling
+    /// new cells.
+    pub fn resize(&mut self, width: usize, height: usize, fill: T) {
+        self.values = translate_values(
+            &self.values,
+            self.width,
+            width,
+            height,
+            Point::new(0, 0),
+            fill,
+        );
+        self.width = width;
+        self.height = height;
+    }
+    /// Gives the part of the grid contained in the rectangle (see [Rect::contains]), clipped
+    /// to the grid, along with the offset of the contents, minus the clipped lower corner.
+    pub fn crop(&self, rect: Rect) -> (SimpleValueGrid<T>, Point) {
+        let clipped = clip_rect(rect, self.width, self.height);
+        let (width, height) = cropped_dimensions(clipped);
+        let grid = SimpleValueGrid {
+            width,
+            height,
+            values: clipped.map_or(vec![], |r| crop_values(&self.values, self.width, r)),
+        };
+        (grid, crop_offset(clipped))
+    }
+    /// Adds filled rows and columns on each side, `top` being the side of increasing `y`.
+    /// Returns the offset of the contents.
+    pub fn pad(&mut self, left: usize, right: usize, top: usize, bottom: usize, fill: T) -> Point {
+        let offset = Point::new(left as i32, bottom as i32);
+        let width = left + self.width + right;
+        let height = bottom + self.height + top;
+        self.values = translate_values(&self.values, self.width, width, height, offset, fill);
+        self.width = width;
+        self.height = height;
+        offset
+    }
+    /// Crops the grid to the bounding box of the cells matching the predicate, leaving an empty
+    /// grid if there are none. Returns the offset of the contents.
+    pub fn shrink_to_fit(&mut self, predicate: impl Fn(T) -> bool) -> Point {
+        let bounds = bounding_rect(self.cells().filter(|(_, v)| predicate(*v)).map(|(p, _)| p));
+        let (grid, offset) = self.crop(bounds.unwrap_or(Rect::new(0, 0, -1, -1)));
+        *self = grid;
+        offset
+    }
+}
+
+impl BoolGrid {
+    /// Builds a grid of the given dimensions holding the cells of this grid translated by the
+    /// offset.
+    fn translated(&self, width: usize, height: usize, offset: Point, fill: bool) -> BoolGrid {
+        let mut grid = BoolGrid::new(width, height, fill);
+        for (p, v) in self.cells() {
+            let q = p + offset;
+            if grid.point_in_bounds(q) {
+                grid.set_point(q, v);
+            }
+        }
+        grid
+    }
+    /// Changes the dimensions of the grid, keeping the values at their coordinates and filling
+    /// new cells.
+    pub fn resize(&mut self, width: usize, height: usize, fill: bool) {
+        *self = self.translated(width, height, Point::new(0, 0), fill);
+    }
+    /// Gives the part of the grid contained in the rectangle (see [Rect::contains]), clipped
+    /// to the grid, along with the offset of the contents, minus the clipped lower corner.
+    pub fn crop(&self, rect: Rect) -> (BoolGrid, Point) {
+        let clipped = clip_rect(rect, self.width, self.height);
+        let (width, height) = cropped_dimensions(clipped);
+        let offset = crop_offset(clipped);
+        (self.translated(width, height, offset, false), offset)
+    }
+    /// Adds filled rows and columns on each side, `top` being the side of increasing `y`.
+    /// Returns the offset of the contents.
+    pub fn pad(
+        &mut self,
+        left: usize,
+        right: usize,
+        top: usize,
+        bottom: usize,
+        fill: bool,
+    ) -> Point {
+        let offset = Point::new(left as i32, bottom as i32);
+        *self = self.translated(
+            left + self.width + right,
+            bottom + self.height + top,
+            offset,
+            fill,
+        );
+        offset
+    }
+    /// Crops the grid to the bounding box of the cells matching the predicate, leaving an empty
+    /// grid if there are none. Returns the offset of the contents.
+    pub fn shrink_to_fit(&mut self, predicate: impl Fn(bool) -> bool) -> Point {
+        let bounds = bounding_rect(self.cells().filter(|(_, v)| predicate(*v)).map(|(p, _)| p));
+        let (grid, offset) = self.crop(bounds.unwrap_or(Rect::new(0, 0, -1, -1)));
+        *self = grid;
+        offset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Grid;
+
+    #[test]
+    fn test_resize_and_pad() {
+        let mut grid = SimpleGrid::from_values(2, 2, vec![1, 2, 3, 4]).unwrap();
+        grid.resize(3, 1, 0);
+        assert_eq!(
+            (grid.width, grid.height, grid.values.clone()),
+            (3, 1, vec![1, 2, 0])
+        );
+        let offset = grid.pad(1, 0, 2, 1, 9);
+        assert_eq!(offset, Point::new(1, 1));
+        assert_eq!((grid.width, grid.height), (4, 4));
+        assert_eq!(grid.get_point(Point::new(1, 0) + offset), Some(&2));
+        assert_eq!(grid.get(0, 0), Some(&9));
+        assert_eq!(grid.get(3, 3), Some(&9));
+    }
+
+    #[test]
+    fn test_crop_and_shrink() {
+        let mut grid: SimpleValueGrid<u8> = ValueGrid::new(6, 5, 0);
+        grid.set(2, 1, 1);
+        grid.set(4, 3, 2);
+        let (cropped, offset) = grid.crop(Rect::new(2, 1, 10, 10));
+        assert_eq!((cropped.width, cropped.height), (4, 4));
+        assert_eq!(cropped.get(2, 2), 2);
+        assert_eq!(offset, Point::new(-2, -1));
+        let (_, offset) = grid.crop(Rect::new(-3, 4, 5, 9));
+        assert_eq!(offset, Point::new(0, -4));
+        let stored = Point::new(4, 3);
+        let offset = grid.shrink_to_fit(|v| v != 0);
+        assert_eq!(offset, Point::new(-2, -1));
+        assert_eq!((grid.width, grid.height), (3, 3));
+        assert_eq!(grid.get_point(stored + offset), 2);
+        assert_eq!(grid.get(0, 0), 1);
+        grid.shrink_to_fit(|v| v > 5);
+        assert_eq!((grid.width, grid.height), (0, 0));
+    }
+
+    #[test]
+    fn test_bool_grid_resize() {
+        let mut grid = BoolGrid::new(70, 3, false);
+        grid.set(65, 1, true);
+        grid.set(3, 2, true);
+        let offset = grid.shrink_to_fit(|v| v);
+        assert_eq!(offset, Point::new(-3, -1));
+        assert_eq!((grid.width, grid.height), (63, 2));
+        assert!(grid.get(62, 0) && grid.get(0, 1));
+        let offset = grid.pad(1, 1, 1, 1, true);
+        assert_eq!(grid.count_ones(), 65 * 4 - 63 * 2 + 2);
+        assert!(grid.get_point(Point::new(62, 0) + offset));
+        grid.resize(2, 2, false);
+        assert_eq!(grid.count_ones(), 3);
+        assert_eq!(grid.crop(Rect::new(1, 1, 5, 5)).0.count_ones(), 0);
+    }
+}
@@ -0,0 +1,262 @@
+//! The eight [Transform]'s mapping a rectangular grid onto itself, along with their
+//! implementations for [SimpleGrid], [SimpleValueGrid] and [BoolGrid].
+
+use crate::direction::Direction;
+use crate::grid::{BoolGrid, SimpleGrid, SimpleValueGrid, ValueGrid};
+use crate::point::Point;
+use serde::{Deserialize, Serialize};
+
+/// Rotation or reflection of a grid. Rotations follow the crate's convention of
+/// [NORTH](Direction::NORTH) pointing towards increasing `y`, so [RotateCw](Self::RotateCw) turns
+/// [NORTH](Direction::NORTH) into [EAST](Direction::EAST).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Transform {
+    Identity,
+    RotateCw,
+    Rotate180,
+    RotateCcw,
+    /// Mirrors left and right.
+    FlipHorizontal,
+    /// Mirrors top and bottom.
+    FlipVertical,
+    /// Swaps `x` and `y`, mirroring along the diagonal through the origin.
+    Transpose,
+    /// Mirrors along the other diagonal.
+    AntiTranspose,
+}
+
+impl Transform {
+    /// All eight transforms, which together give every orientation of a grid.
+    pub const ALL: [Transform; 8] = [
+        Transform::Identity,
+        Transform::RotateCw,
+        Transform::Rotate180,
+        Transform::RotateCcw,
+        Transform::FlipHorizontal,
+        Transform::FlipVertical,
+        Transform::Transpose,
+        Transform::AntiTranspose,
+    ];
+    /// Whether the transform swaps the width and height of a grid.
+    pub fn swaps_dimensions(&self) -> bool {
+        matches!(
+            self,
+            Transform::RotateCw
+                | Transform::RotateCcw
+                | Transform::Transpose
+                | Transform::AntiTranspose
+        )
+    }
+    /// Gives the dimensions of a grid of the given dimensions after the transform.
+    pub fn dimensions(&self, width: usize, height: usize) -> (usize, usize) {
+        if self.swaps_dimensions() {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+    /// The transform undoing this one.
+    pub fn inverse(&self) -> Transform {
+        match self {
+            Transform::RotateCw => Transform::RotateCcw,
+            Transform::RotateCcw => Transform::RotateCw,
+            t => *t,
+        }
+    }
+    /// Maps a point of a grid with the given dimensions to its position in the transformed grid.
+    pub fn apply_point(&self, point: Point, width: usize, height: usize) -> Point {
+        let (x, y) = (point.x, point.y);
+        let w = width as i32 - 1;
+        let h = height as i32 - 1;
+        match self {
+            Transform::Identity => Point::new(x, y),
+            Transform::RotateCw => Point::new(y, w - x),
+            Transform::Rotate180 => Point::new(w - x, h - y),
+            Transform::RotateCcw => Point::new(h - y, x),
+            Transform::FlipHorizontal => Point::new(w - x, y),
+            Transform::FlipVertical => Point::new(x, h - y),
+            Transform::Transpose => Point::new(y, x),
+            Transform::AntiTranspose => Point::new(h - y, w - x),
+        }
+    }
+    /// Maps a direction to its counterpart in the transformed grid.
+    pub fn apply_direction(&self, dir: Direction) -> Direction {
+        // Transforming around a single cell leaves only the linear part of the mapping.
+        self.apply_point(Point::from(dir), 1, 1).abs_dir()
+    }
+    /// Builds the row-major values of the transformed grid, reading source values by their
+    /// coordinates.
+    fn remap<T>(&self, width: usize, height: usize, get: impl Fn(Point) -> T) -> Vec<T> {
+        let (new_width, new_height) = self.dimensions(width, height);
+        let inverse = self.inverse();
+        (0..new_height as i32)
+            .flat_map(|y| (0..new_width as i32).map(move |x| Point::new(x, y)))
+            .map(|p| get(inverse.apply_point(p, new_width, new_height)))
+            .collect()
+    }
+}
+
+impl Point {
+    /// Maps this point of a grid with the given dimensions to its position in the grid after the
+    /// transform, consistent with e.g. [SimpleGrid::transform].
+    pub fn transform(&self, transform: Transform, width: usize, height: usize) -> Point {
+        transform.apply_point(*self, width, height)
+    }
+}
+
+impl<T: Clone> SimpleGrid<T> {
+    /// Gives the grid after the transform, with width and height swapped as appropriate.
+    pub fn transform(&self, transform: Transform) -> SimpleGrid<T> {
+        let (width, height) = transform.dimensions(self.width, self.height);
+        let values = transform.remap(self.width, self.height, |p| {
+            self.values[p.x as usize + p.y as usize * self.width].clone()
+        });
+        SimpleGrid {
+            width,
+            height,
+            values,
+        }
+    }
+    pub fn rotate_cw(&self) -> SimpleGrid<T> {
+        self.transform(Transform::RotateCw)
+    }
+    pub fn rotate_ccw(&self) -> SimpleGrid<T> {
+        self.transform(Transform::RotateCcw)
+    }
+    pub fn rotate_180(&self) -> SimpleGrid<T> {
+        self.transform(Transform::Rotate180)
+    }
+    pub fn flip_horizontal(&self) -> SimpleGrid<T> {
+        self.transform(Transform::FlipHorizontal)
+    }
+    pub fn flip_vertical(&self) -> SimpleGrid<T> {
+        self.transform(Transform::FlipVertical)
+    }
+    pub fn transpose(&self) -> SimpleGrid<T> {
+        self.transform(Transform::Transpose)
+    }
+}
+
+impl<T: Clone + Copy> SimpleValueGrid<T> {
+    /// Gives the grid after the transform, with width and height swapped as appropriate.
+    pub fn transform(&self, transform: Transform) -> SimpleValueGrid<T> {
+        let (width, height) = transform.dimensions(self.width, self.height);
+        let values = transform.remap(self.width, self.height, |p| self.get_point(p));
+        SimpleValueGrid {
+            width,
+            height,
+            values,
+        }
+    }
+    pub fn rotate_cw(&self) -> SimpleValueGrid<T> {
+        self.transform(Transform::RotateCw)
+    }
+    pub fn rotate_ccw(&self) -> SimpleValueGrid<T> {
+        self.transform(Transform::RotateCcw)
+    }
+    pub fn rotate_180(&self) -> SimpleValueGrid<T> {
+        self.transform(Transform::Rotate180)
+    }
+    pub fn flip_horizontal(&self) -> SimpleValueGrid<T> {
+        self.transform(Transform::FlipHorizontal)
+    }
+    pub fn flip_vertical(&self) -> SimpleValueGrid<T> {
+        self.transform(Transform::FlipVertical)
+    }
+    pub fn transpose(&self) -> SimpleValueGrid<T> {
+        self.transform(Transform::Transpose)
+    }
+}
+
+impl BoolGrid {
+    /// Gives the grid after the transform, with width and height swapped as appropriate.
+    pub fn transform(&self, transform: Transform) -> BoolGrid {
+        let (width, height) = transform.dimensions(self.width, self.height);
+        let mut grid = BoolGrid::new(width, height, false);
+        for p in self.iter_ones() {
+            grid.set_point(transform.apply_point(p, self.width, self.height), true);
+        }
+        grid
+    }
+    pub fn rotate_cw(&self) -> BoolGrid {
+        self.transform(Transform::RotateCw)
+    }
+    pub fn rotate_ccw(&self) -> BoolGrid {
+        self.transform(Transform::RotateCcw)
+    }
+    pub fn rotate_180(&self) -> BoolGrid {
+        self.transform(Transform::Rotate180)
+    }
+    pub fn flip_horizontal(&self) -> BoolGrid {
+        self.transform(Transform::FlipHorizontal)
+    }
+    pub fn flip_vertical(&self) -> BoolGrid {
+        self.transform(Transform::FlipVertical)
+    }
+    pub fn transpose(&self) -> BoolGrid {
+        self.transform(Transform::Transpose)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Grid;
+
+    fn numbered(width: usize, height: usize) -> SimpleGrid<usize> {
+        SimpleGrid::from_values(width, height, (0..width * height).collect()).unwrap()
+    }
+
+    #[test]
+    fn test_rotate_cw() {
+        // 2 3      0 2
+        // 0 1  ->  1 3 (rows listed top to bottom)
+        let grid = numbered(2, 2).rotate_cw();
+        assert_eq!(grid.values, vec![1, 3, 0, 2]);
+        let wide = numbered(3, 2).rotate_cw();
+        assert_eq!((wide.width, wide.height), (2, 3));
+        assert_eq!(*wide.get(0, 2).unwrap(), 0);
+        assert_eq!(
+            Transform::RotateCw.apply_direction(Direction::NORTH),
+            Direction::EAST
+        );
+        assert_eq!(
+            Transform::FlipVertical.apply_direction(Direction::NORTHEAST),
+            Direction::SOUTHEAST
+        );
+    }
+
+    #[test]
+    fn test_point_transform_matches_grid() {
+        let grid = numbered(4, 3);
+        for t in Transform::ALL {
+            let transformed = grid.transform(t);
+            for (p, v) in grid.cells() {
+                let q = p.transform(t, grid.width, grid.height);
+                assert_eq!(transformed.get_point(q), Some(v), "{:?} of {}", t, p);
+            }
+            assert_eq!(transformed.transform(t.inverse()).values, grid.values);
+        }
+        assert_eq!(
+            grid.rotate_cw().rotate_cw().values,
+            grid.rotate_180().values
+        );
+        assert_eq!(
+            grid.transpose().rotate_ccw().values,
+            grid.flip_horizontal().values
+        );
+    }
+
+    #[test]
+    fn test_bool_grid_transform() {
+        let mut grid = BoolGrid::new(67, 3, false);
+        grid.set(66, 0, true);
+        grid.set(1, 2, true);
+        for t in Transform::ALL {
+            let transformed = grid.transform(t);
+            assert_eq!(transformed.count_ones(), 2);
+            assert_eq!(transformed.transform(t.inverse()), grid);
+        }
+        assert!(grid.rotate_cw().get(0, 0));
+    }
+}
@@ -0,0 +1,245 @@
+//! Validation of deserialized grids, so that corrupted data gives an error when it is read rather
+//! than a grid which panics when it is accessed.
+//!
+//! Deserializing a [SimpleGrid], [SimpleValueGrid], [LayoutGrid] or [BoolGrid] checks that its
+//! values fit its dimensions and that it has at most [max_deserialized_cells] cells, reporting a
+//! [GridError] through the deserializer otherwise. A [ChunkedGrid] is checked likewise for the
+//! values of each chunk and the cells of all chunks together.
+
+use crate::chunked::{ChunkedGrid, CHUNK_SIZE};
+use crate::error::GridError;
+use crate::grid::{BoolGrid, SimpleGrid, SimpleValueGrid};
+use crate::layout::{Layout, LayoutGrid};
+use crate::point::Point;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static MAX_DESERIALIZED_CELLS: AtomicUsize = AtomicUsize::new(1 << 28);
+
+thread_local! {
+    /// Limit set by [with_max_deserialized_cells] on this thread, taking precedence over
+    /// [MAX_DESERIALIZED_CELLS].
+    static SCOPED_MAX_DESERIALIZED_CELLS: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// The largest number of cells a deserialized grid may have. This is the limit given to the
+/// innermost [with_max_deserialized_cells] running on this thread, if any, and otherwise the
+/// default of `2^28` unless changed by [set_max_deserialized_cells].
+pub fn max_deserialized_cells() -> usize {
+    SCOPED_MAX_DESERIALIZED_CELLS
+        .with(|scoped| scoped.get())
+        .unwrap_or_else(|| MAX_DESERIALIZED_CELLS.load(Ordering::Relaxed))
+}
+
+/// Sets the default largest number of cells a deserialized grid may have, for all threads.
+pub fn set_max_deserialized_cells(limit: usize) {
+    MAX_DESERIALIZED_CELLS.store(limit, Ordering::Relaxed);
+}
+
+/// Runs `f` with the largest number of cells a deserialized grid may have set to `limit`, so that
+/// a single call can accept larger grids or guard against untrusted input without affecting other
+/// threads. The previous limit is restored afterwards, even if `f` panics.
+pub fn with_max_deserialized_cells<R>(limit: usize, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<usize>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SCOPED_MAX_DESERIALIZED_CELLS.with(|scoped| scoped.set(self.0));
+        }
+    }
+    let _restore =
+        Restore(SCOPED_MAX_DESERIALIZED_CELLS.with(|scoped| scoped.replace(Some(limit))));
+    f()
+}
+
+/// Checks that a grid of the given dimensions is within [max_deserialized_cells], giving its
+/// number of cells.
+pub(crate) fn check_size(width: usize, height: usize) -> Result<usize, GridError> {
+    let limit = max_deserialized_cells();
+    match width.checked_mul(height) {
+        Some(cells) if cells <= limit => Ok(cells),
+        _ => Err(GridError::TooLarge {
+            width,
+            height,
+            limit,
+        }),
+    }
+}
+
+/// Fields of a [SimpleGrid], [SimpleValueGrid] or [LayoutGrid] as read before validation, and as
+/// written for a [LayoutGrid].
+#[derive(Serialize, Deserialize)]
+pub(crate) struct GridData<T> {
+    width: usize,
+    height: usize,
+    values: Vec<T>,
+}
+
+/// Fields of a [BoolGrid] as read before validation.
+#[derive(Deserialize)]
+pub(crate) struct BoolGridData {
+    width: usize,
+    height: usize,
+    values: Vec<u64>,
+}
+
+/// Fields of a [ChunkedGrid] as read before validation.
+#[derive(Deserialize)]
+pub(crate) struct ChunkedGridData<T> {
+    default_value: T,
+    chunks: HashMap<Point, Vec<T>>,
+}
+
+impl<T> TryFrom<GridData<T>> for SimpleGrid<T> {
+    type Error = GridError;
+    fn try_from(data: GridData<T>) -> Result<Self, Self::Error> {
+        check_size(data.width, data.height)?;
+        SimpleGrid::from_values(data.width, data.height, data.values)
+    }
+}
+
+impl<T: Clone + Copy> TryFrom<GridData<T>> for SimpleValueGrid<T> {
+    type Error = GridError;
+    fn try_from(data: GridData<T>) -> Result<Self, Self::Error> {
+        check_size(data.width, data.height)?;
+        SimpleValueGrid::from_values(data.width, data.height, data.values)
+    }
+}
+
+impl<T: Clone, L: Layout> TryFrom<GridData<T>> for LayoutGrid<T, L> {
+    type Error = GridError;
+    fn try_from(data: GridData<T>) -> Result<Self, Self::Error> {
+        check_size(data.width, data.height)?;
+        LayoutGrid::from_values(data.width, data.height, data.values)
+    }
+}
+
+impl<T: Clone, L: Layout> From<&LayoutGrid<T, L>> for GridData<T> {
+    fn from(grid: &LayoutGrid<T, L>) -> Self {
+        GridData {
+            width: grid.width,
+            height: grid.height,
+            values: grid.to_values(),
+        }
+    }
+}
+
+impl<T> TryFrom<ChunkedGridData<T>> for ChunkedGrid<T> {
+    type Error = GridError;
+    fn try_from(data: ChunkedGridData<T>) -> Result<Self, Self::Error> {
+        // The chunks are counted as if stacked into a single column.
+        check_size(CHUNK_SIZE, CHUNK_SIZE * data.chunks.len())?;
+        for values in data.chunks.values() {
+            if values.len() != CHUNK_SIZE * CHUNK_SIZE {
+                return Err(GridError::InvalidDimensions {
+                    width: CHUNK_SIZE,
+                    height: CHUNK_SIZE,
+                    len: values.len(),
+                });
+            }
+        }
+        Ok(ChunkedGrid::from_chunks(data.default_value, data.chunks))
+    }
+}
+
+impl TryFrom<BoolGridData> for BoolGrid {
+    type Error = GridError;
+    fn try_from(data: BoolGridData) -> Result<Self, Self::Error> {
+        let (width, height) = (data.width, data.height);
+        let cells = check_size(width, height)?;
+        if data.values.len() != 1 + cells / 64 {
+            return Err(GridError::InvalidWordCount {
+                width,
+                height,
+                words: data.values.len(),
+            });
+        }
+        let grid = BoolGrid {
+            width,
+            height,
+            values: data.values,
+        };
+        if grid.has_padding_set() {
+            return Err(GridError::PaddingSet { width, height });
+        }
+        Ok(grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Grid;
+
+    #[test]
+    fn test_validated_deserialize() {
+        let grid: SimpleGrid<u8> = ron::from_str("(width: 2, height: 1, values: [1, 2])").unwrap();
+        assert_eq!(grid.values, vec![1, 2]);
+        let err = ron::from_str::<SimpleGrid<u8>>("(width: 2, height: 2, values: [1, 2])")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("2 values do not fit a 2x2 grid"), "{}", err);
+        assert!(
+            ron::from_str::<SimpleValueGrid<u8>>("(width: 3, height: 1, values: [1])").is_err()
+        );
+
+        for bools in [BoolGrid::new(10, 7, true), BoolGrid::default()] {
+            assert_eq!(
+                ron::from_str::<BoolGrid>(&ron::to_string(&bools).unwrap()).unwrap(),
+                bools
+            );
+        }
+        let err = ron::from_str::<BoolGrid>("(width: 8, height: 8, values: [0])")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("1 words do not fit a 8x8 BoolGrid"), "{}", err);
+        let err = ron::from_str::<BoolGrid>("(width: 2, height: 2, values: [16])")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("bits past the cells"), "{}", err);
+        let err =
+            ron::from_str::<BoolGrid>(&format!("(width: {}, height: 2, values: [])", usize::MAX))
+                .unwrap_err()
+                .to_string();
+        assert!(err.contains("exceeds the limit"), "{}", err);
+    }
+
+    #[test]
+    fn test_scoped_limit() {
+        let text =
+            "(width: 4, height: 4, values: [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0])";
+        let default = max_deserialized_cells();
+        let err = with_max_deserialized_cells(10, || {
+            assert_eq!(max_deserialized_cells(), 10);
+            // The innermost limit applies.
+            assert!(with_max_deserialized_cells(16, || {
+                ron::from_str::<SimpleValueGrid<u8>>(text)
+            })
+            .is_ok());
+            ron::from_str::<SimpleValueGrid<u8>>(text).unwrap_err()
+        });
+        assert!(
+            err.to_string().contains("exceeds the limit of 10"),
+            "{}",
+            err
+        );
+        assert_eq!(max_deserialized_cells(), default);
+    }
+
+    #[test]
+    fn test_validated_chunked_deserialize() {
+        let mut chunked = ChunkedGrid::empty(0u8);
+        chunked.set(-40, 3, 7);
+        let text = ron::to_string(&chunked).unwrap();
+        assert!(!text.contains("bounds"), "{}", text);
+        let read: ChunkedGrid<u8> = ron::from_str(&text).unwrap();
+        assert_eq!(read.get(-40, 3), Some(&7));
+        assert_eq!(read.bounding_rect(), chunked.bounding_rect());
+        let err =
+            ron::from_str::<ChunkedGrid<u8>>("(default_value: 0, chunks: {(x: 0, y: 0): [1]})")
+                .unwrap_err()
+                .to_string();
+        assert!(err.contains("1 values do not fit a 32x32 grid"), "{}", err);
+    }
+}